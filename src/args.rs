@@ -1,10 +1,13 @@
 use clap; // an argument parser
 
+use crate::render::headless::{RenderOptions, RenderStyle};
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Args {
     pub filename: Option<String>,
     pub headless_mode: HeadlessMode,
     pub no_contour_ops: bool,
+    pub render_options: Option<RenderOptions>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -13,6 +16,7 @@ pub enum HeadlessMode {
     Flatten,
     Export,
     Save,
+    Render,
     RunScript, // unused until scripting support added
 }
 
@@ -56,6 +60,49 @@ pub fn parse_args() -> Args {
                 .takes_value(false)
                 .help(r#"For either the save or the export operation, remove all contour operations, don't apply them."#)
         )
+        .arg(
+            clap::Arg::new("render")
+                .long("render")
+                .short('r')
+                .takes_value(true)
+                .value_name("OUT")
+                .conflicts_with_all(&["save", "export", "flatten"])
+                .help(r#"Render the glyph preview to a .png or .svg file, without opening a window"#)
+        )
+        .arg(
+            clap::Arg::new("render-size")
+                .long("render-size")
+                .takes_value(true)
+                .default_value("512")
+                .validator(|s| s.parse::<u32>().map(|_| ()).map_err(|e| e.to_string()))
+                .requires("render")
+                .help(r#"Height of the rendered image in pixels"#)
+        )
+        .arg(
+            clap::Arg::new("render-padding")
+                .long("render-padding")
+                .takes_value(true)
+                .default_value("16")
+                .validator(|s| s.parse::<u32>().map(|_| ()).map_err(|e| e.to_string()))
+                .requires("render")
+                .help(r#"Empty space around the rendered glyph in pixels"#)
+        )
+        .arg(
+            clap::Arg::new("render-style")
+                .long("render-style")
+                .takes_value(true)
+                .possible_values(&["fill", "outline", "paper"])
+                .default_value("fill")
+                .requires("render")
+                .help(r#"How to draw the rendered glyph"#)
+        )
+        .arg(
+            clap::Arg::new("render-points")
+                .long("render-points")
+                .takes_value(false)
+                .requires("render")
+                .help(r#"Draw points and handles on the rendered glyph"#)
+        )
         .get_matches();
 
     let headless_mode = if matches.is_present("export") {
@@ -64,16 +111,28 @@ pub fn parse_args() -> Args {
         HeadlessMode::Flatten
     } else if matches.is_present("save") {
         HeadlessMode::Save
+    } else if matches.is_present("render") {
+        HeadlessMode::Render
     } else {
         HeadlessMode::None
     };
 
     let no_contour_ops = matches.is_present("no-contour-ops");
 
+    // clap has validated all of these already, so unwrapping is fine.
+    let render_options = matches.value_of("render").map(|output| RenderOptions {
+        output: output.into(),
+        size: matches.value_of("render-size").unwrap().parse().unwrap(),
+        padding: matches.value_of("render-padding").unwrap().parse().unwrap(),
+        style: matches.value_of("render-style").unwrap().parse::<RenderStyle>().unwrap(),
+        points: matches.is_present("render-points"),
+    });
+
     let args = Args {
         filename: matches.value_of("GLIF").map(|s| s.to_string()),
         headless_mode,
         no_contour_ops,
+        render_options,
     };
 
     args
@@ -8,7 +8,7 @@ use std::{
 };
 use std::{env, fs};
 
-use strum::{Display, EnumString};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator as _};
 
 #[derive(Copy, Clone, EnumString, Hash, Display, Debug, PartialEq, Eq)]
 pub enum CommandType {
@@ -27,7 +27,7 @@ pub enum CommandType {
 
 // a command file is put into the user's config directory upon first run
 // <command name="ToolPen" key = "A">
-#[derive(Copy, Clone, EnumString, Hash, Display, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, EnumString, EnumIter, Hash, Display, Debug, PartialEq, Eq)]
 pub enum Command {
     // zoom
     ResetScale,
@@ -60,6 +60,7 @@ pub enum Command {
     ToolDash,
     ToolPAP,
     ToolVWS,
+    ToolNib,
     ToolMeasure,
     ToolAnchors,
     ToolShapes,
@@ -71,6 +72,8 @@ pub enum Command {
     DeleteSelection,
     SelectAll,
     CopySelection,
+    CopySelectionAsGlif,
+    CopySelectionAsSvg,
     PasteSelection,
     PasteSelectionInPlace,
     CutSelection,
@@ -97,6 +100,10 @@ pub enum Command {
     // misc
     Quit,
     ReverseContour,
+    HarmonizeSelection,
+    HarmonizeSelectionMovingPoints,
+    BalanceSelection,
+    ExpandContourOperations,
 
     // debug
     SkiaDump,
@@ -110,13 +117,15 @@ impl Command {
             NudgeUp | NudgeBigUp | NudgeTinyUp | NudgeDown | NudgeBigDown | NudgeTinyDown
             | NudgeLeft | NudgeBigLeft | NudgeTinyLeft | NudgeRight | NudgeBigRight
             | NudgeTinyRight => CommandType::Nudge,
-            ToolPan | ToolPen | ToolSelect | ToolZoom | ToolDash | ToolPAP | ToolVWS
+            ToolPan | ToolPen | ToolSelect | ToolZoom | ToolDash | ToolPAP | ToolVWS | ToolNib
             | ToolMeasure | ToolAnchors | ToolShapes | ToolGuidelines | ToolImages => {
                 CommandType::ToolSelect
             }
             DeleteSelection
             | SelectAll
             | CopySelection
+            | CopySelectionAsGlif
+            | CopySelectionAsSvg
             | PasteSelection
             | PasteSelectionInPlace
             | CutSelection => CommandType::Selection,
@@ -125,7 +134,11 @@ impl Command {
             ToolGrid | TogglePointLabels | TogglePreviewMode => CommandType::ViewMode,
             ToggleConsole => CommandType::ToggleConsole,
             Quit => CommandType::ExecState,
-            ReverseContour => CommandType::PathOp,
+            ReverseContour
+            | HarmonizeSelection
+            | HarmonizeSelectionMovingPoints
+            | BalanceSelection
+            | ExpandContourOperations => CommandType::PathOp,
             SkiaDump => CommandType::Debug,
         }
     }
@@ -143,6 +156,21 @@ impl CommandMod {
     pub fn none() -> Self {
         Self::default()
     }
+
+    /// The modifiers as they're written in the keybindings XML, e.g. "CtrlShiftMod".
+    pub fn to_xml(&self) -> Option<String> {
+        if *self == Self::none() {
+            return None;
+        }
+        let mut s = String::new();
+        for (held, name) in [(self.ctrl, "Ctrl"), (self.shift, "Shift"), (self.alt, "Alt"), (self.meta, "Meta")] {
+            if held {
+                s.push_str(name);
+            }
+        }
+        s.push_str("Mod");
+        Some(s)
+    }
 }
 
 impl From<&str> for CommandMod {
@@ -192,6 +220,51 @@ fn command_mod_test() {
     );
 }
 
+/// One key pressed with some modifiers. A binding is a sequence of these: most are one key, but
+/// chords, like Ctrl+K then C, are more.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    pub keycode: Keycode,
+    pub command_mod: CommandMod,
+}
+
+impl std::fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [
+            (self.command_mod.ctrl, "Ctrl"),
+            (self.command_mod.shift, "Shift"),
+            (self.command_mod.alt, "Alt"),
+            (self.command_mod.meta, "Meta"),
+        ] {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.keycode.name())
+    }
+}
+
+pub type KeySequence = Vec<KeyPress>;
+
+pub fn key_sequence_to_string(sequence: &[KeyPress]) -> String {
+    sequence.iter().map(|k| k.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Whether two bindings can't both work: they're the same, or one's the start of the other, so the
+/// longer could never be finished.
+pub fn sequences_conflict(a: &[KeyPress], b: &[KeyPress]) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Keys that are only ever held with others, and so can't start or continue a binding by
+/// themselves.
+pub fn is_modifier(keycode: Keycode) -> bool {
+    matches!(
+        keycode,
+        Keycode::LCtrl | Keycode::RCtrl | Keycode::LShift | Keycode::RShift | Keycode::LAlt | Keycode::RAlt | Keycode::LGui | Keycode::RGui
+    )
+}
+
 pub struct CommandInfo {
     pub command: Command,
     pub command_mod: CommandMod,
@@ -222,39 +295,86 @@ fn load_keybinding_xml(ignore_local: bool) -> Result<String, Box<dyn std::error:
     })
 }
 
-fn parse_keybinds(
-    mut config: xmltree::Element,
-) -> Result<HashMap<(Keycode, CommandMod), Command>, String> {
-    let mut hm: HashMap<(Keycode, CommandMod), Command> = HashMap::new();
+fn parse_key_press(element: &xmltree::Element) -> Result<KeyPress, String> {
+    let keycode = element
+        .attributes
+        .get("key")
+        .map(|k| Ok(k))
+        .unwrap_or(Err("Binding does not have a key associated!"))?;
+    let command_mod = element.attributes.get("mod").map(|m| m.as_str()).unwrap_or("").into();
+    let keycode = sdl2::keyboard::Keycode::from_name(keycode)
+        .map(|kc| Ok(kc))
+        .unwrap_or(Err(format!("Invalid keycode string {:?}!", keycode)))?;
+    Ok(KeyPress { keycode, command_mod })
+}
+
+/// Bindings look like <binding command="ToolPen" key="P"/>. A chord has the keys after its first
+/// as <then key="…" mod="…"/> children.
+fn parse_keybinds(mut config: xmltree::Element) -> Result<HashMap<KeySequence, Command>, String> {
+    let mut hm: HashMap<KeySequence, Command> = HashMap::new();
 
     while let Some(binding) = config.take_child("binding") {
-        let keycode = binding
-            .attributes
-            .get("key")
-            .map(|k| Ok(k))
-            .unwrap_or(Err("Binding does not have a key associated!"))?;
         let command = binding
             .attributes
             .get("command")
             .map(|c| Ok(c))
             .unwrap_or(Err("Binding does not have a command associated!"))?;
-        let modifier = binding.attributes.get("mod");
-
-        let command_mod = modifier.map(|m| m.as_str()).unwrap_or("").into();
 
         let command_enum = Command::from_str(command)
             .map(|c| Ok(c))
-            .unwrap_or(Err("Invalid command string!"))?;
-        let keycode_enum = sdl2::keyboard::Keycode::from_name(keycode)
-            .map(|kc| Ok(kc))
-            .unwrap_or(Err("Invalid keycode string!"))?;
+            .unwrap_or(Err(format!("Invalid command string {:?}!", command)))?;
 
-        hm.insert((keycode_enum, command_mod), command_enum);
+        let mut sequence = vec![parse_key_press(&binding)?];
+        for then in binding.children.iter().filter_map(|c| c.as_element()).filter(|e| e.name == "then") {
+            sequence.push(parse_key_press(then)?);
+        }
+
+        hm.insert(sequence, command_enum);
     }
 
     Ok(hm)
 }
 
+/// Bindings as a list in the order of enum Command, for showing and writing back.
+fn sorted_keybinds(keybinds: &HashMap<KeySequence, Command>) -> Vec<(Command, KeySequence)> {
+    let order: HashMap<Command, usize> = Command::iter().enumerate().map(|(i, c)| (c, i)).collect();
+    let mut ret: Vec<(Command, KeySequence)> = keybinds.iter().map(|(seq, c)| (*c, seq.clone())).collect();
+    ret.sort_by_key(|(c, seq)| (order[c], key_sequence_to_string(seq)));
+    ret
+}
+
+fn key_press_element(name: &str, key: &KeyPress) -> xmltree::Element {
+    let mut element = xmltree::Element::new(name);
+    element.attributes.insert("key".to_string(), key.keycode.name());
+    if let Some(m) = key.command_mod.to_xml() {
+        element.attributes.insert("mod".to_string(), m);
+    }
+    element
+}
+
+fn keybinds_to_xml(keybinds: &[(Command, KeySequence)]) -> Result<String, String> {
+    let mut root = xmltree::Element::new("keybindings");
+    root.children.push(xmltree::XMLNode::Comment(
+        " For a list of commands, see file src/command/mod.rs enum Command ".to_string(),
+    ));
+    for (command, sequence) in keybinds {
+        let (first, rest) = match sequence.split_first() {
+            Some(split) => split,
+            None => continue,
+        };
+        let mut binding = key_press_element("binding", first);
+        binding.attributes.insert("command".to_string(), command.to_string());
+        for key in rest {
+            binding.children.push(xmltree::XMLNode::Element(key_press_element("then", key)));
+        }
+        root.children.push(xmltree::XMLNode::Element(binding));
+    }
+    let mut out = vec![];
+    root.write_with_config(&mut out, xmltree::EmitterConfig::new().perform_indent(true))
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
 pub fn initialize_keybinds() {
     let default_xml = load_keybinding_xml(true).unwrap();
     let binding_xml = load_keybinding_xml(false);
@@ -275,15 +395,19 @@ pub fn initialize_keybinds() {
         log::warn!("Keybinds XML invalid ({}), using default", e);
         default_keybinds.clone()
     });
-    let default_commands: HashSet<_> = default_keybinds.values().into_iter().collect();
-    let commands: HashSet<_> = keybinds.values().into_iter().collect();
-
-    if commands != default_commands {
-        log::warn!(
-            "Your keybinds are incomplete, missing {:?}; using defaults for all",
-            &default_commands - &commands
-        );
-        keybinds = default_keybinds;
+
+    // Commands added since the user's keybinds were written get their default bindings, as long
+    // as those don't clash with the user's.
+    let commands: HashSet<Command> = keybinds.values().copied().collect();
+    let mut missing = vec![];
+    for (sequence, command) in &default_keybinds {
+        if !commands.contains(command) && !keybinds.keys().any(|s| sequences_conflict(s, sequence)) {
+            missing.push(*command);
+            keybinds.insert(sequence.clone(), *command);
+        }
+    }
+    if !missing.is_empty() {
+        log::warn!("Your keybinds are incomplete, using defaults for {:?}", missing);
     }
 
     if env::var("NO_WRITE_DEFAULT_KEYBINDS").is_err() && binding_xml.is_err() {
@@ -307,7 +431,57 @@ pub fn initialize_keybinds() {
     }
 
     KEYMAP.with(|v| {
-        v.borrow_mut().keybindings = keybinds;
+        let mut v = v.borrow_mut();
+        v.keybindings = keybinds;
+        v.defaults = default_keybinds;
+    })
+}
+
+/// The bindings in use.
+pub fn keybindings() -> Vec<(Command, KeySequence)> {
+    KEYMAP.with(|v| sorted_keybinds(&v.borrow().keybindings))
+}
+
+/// The bindings in resources/default_keymap.xml.
+pub fn default_keybindings() -> Vec<(Command, KeySequence)> {
+    KEYMAP.with(|v| sorted_keybinds(&v.borrow().defaults))
+}
+
+/// Starts using the given bindings, and writes them to the user's keybindings XML.
+pub fn save_keybindings(keybinds: &[(Command, KeySequence)]) -> Result<(), String> {
+    let keybinds: HashMap<KeySequence, Command> = keybinds.iter().map(|(c, seq)| (seq.clone(), *c)).collect();
+    let xml = keybinds_to_xml(&sorted_keybinds(&keybinds))?;
+    fs::write(&*CONFIG_KEYBIND_PATH, xml)
+        .map_err(|e| format!("Could not write keybinds to `{}`: {}", CONFIG_KEYBIND_PATH.display(), e))?;
+    KEYMAP.with(|v| v.borrow_mut().keybindings = keybinds);
+    Ok(())
+}
+
+/// Until stop_recording, keys pressed are kept instead of running commands, so they can be bound.
+pub fn start_recording() {
+    KEYMAP.with(|v| v.borrow_mut().recording = Some(vec![]));
+}
+
+/// The keys pressed so far while recording.
+pub fn recording() -> Option<KeySequence> {
+    KEYMAP.with(|v| v.borrow().recording.clone())
+}
+
+pub fn stop_recording() -> Option<KeySequence> {
+    KEYMAP.with(|v| v.borrow_mut().recording.take())
+}
+
+/// Keeps the key if recording. Returns whether it was kept, in which case it shouldn't do anything
+/// else.
+pub fn record_keypress(keycode: &Keycode, keys_down: &HashSet<Keycode>) -> bool {
+    KEYMAP.with(|v| match v.borrow_mut().recording.as_mut() {
+        Some(recording) => {
+            if !is_modifier(*keycode) {
+                recording.push(KeyPress { keycode: *keycode, command_mod: CommandMod::from_keys_down(keys_down) });
+            }
+            true
+        }
+        None => false,
     })
 }
 
@@ -323,16 +497,30 @@ impl CommandMod {
 }
 
 pub fn keycode_to_command(keycode: &Keycode, keys_down: &HashSet<Keycode>) -> Option<CommandInfo> {
+    if is_modifier(*keycode) {
+        return None;
+    }
+    let press = KeyPress { keycode: *keycode, command_mod: CommandMod::from_keys_down(keys_down) };
+
     let command_enum = KEYMAP.with(|v| {
-        if let Some(key) = v
-            .borrow()
-            .keybindings
-            .get(&(*keycode, CommandMod::from_keys_down(keys_down)))
-        {
-            return Some(*key);
+        let mut v = v.borrow_mut();
+        let mut sequence = std::mem::take(&mut v.pending);
+        sequence.push(press);
+        loop {
+            if let Some(command) = v.keybindings.get(&sequence) {
+                return Some(*command);
+            }
+            // Part of the way through a chord.
+            if v.keybindings.keys().any(|s| s.len() > sequence.len() && s.starts_with(&sequence)) {
+                v.pending = sequence;
+                return None;
+            }
+            // A key that doesn't continue the chord started over with.
+            if sequence.len() == 1 {
+                return None;
+            }
+            sequence = vec![press];
         }
-
-        None
     });
 
     if let Some(command_enum) = command_enum {
@@ -346,9 +534,36 @@ pub fn keycode_to_command(keycode: &Keycode, keys_down: &HashSet<Keycode>) -> Op
 }
 
 struct KeyData {
-    keybindings: HashMap<(Keycode, CommandMod), Command>,
+    keybindings: HashMap<KeySequence, Command>,
+    defaults: HashMap<KeySequence, Command>,
+    // The keys of a chord pressed so far.
+    pending: KeySequence,
+    recording: Option<KeySequence>,
 }
 
 thread_local! {
-    static KEYMAP: RefCell<KeyData> = RefCell::new(KeyData{ keybindings: HashMap::new() });
+    static KEYMAP: RefCell<KeyData> = RefCell::new(KeyData {
+        keybindings: HashMap::new(),
+        defaults: HashMap::new(),
+        pending: vec![],
+        recording: None,
+    });
+}
+
+#[test]
+fn keybinds_xml_test() {
+    let xml = r#"<keybindings>
+        <binding command="ToolPen" key="P"/>
+        <binding command="SkiaDump" key="K" mod="CtrlMod"><then key="D" mod="ShiftMod"/></binding>
+    </keybindings>"#;
+    let keybinds = parse_keybinds(xmltree::Element::parse(xml.as_bytes()).unwrap()).unwrap();
+    let chord = vec![
+        KeyPress { keycode: Keycode::K, command_mod: CommandMod { ctrl: true, ..CommandMod::none() } },
+        KeyPress { keycode: Keycode::D, command_mod: CommandMod { shift: true, ..CommandMod::none() } },
+    ];
+    assert_eq!(keybinds.get(&chord), Some(&Command::SkiaDump));
+    assert_eq!(key_sequence_to_string(&chord), "Ctrl+K, Shift+D");
+    // Written back, the bindings read the same.
+    let written = keybinds_to_xml(&sorted_keybinds(&keybinds)).unwrap();
+    assert_eq!(parse_keybinds(xmltree::Element::parse(written.as_bytes()).unwrap()).unwrap(), keybinds);
 }
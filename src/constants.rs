@@ -5,6 +5,71 @@ pub static OFFSET_FACTOR: f32 = 10.;
 pub static BIG_OFFSET_FACTOR: f32 = OFFSET_FACTOR * 10.;
 pub static TINY_OFFSET_FACTOR: f32 = 1.;
 
+/* Smart guides */
+/// How close, in screen pixels, a dragged point must be to another coordinate to be considered
+/// aligned with it.
+pub static SMART_GUIDE_TOLERANCE: f32 = 3.;
+pub static SMART_GUIDE_STROKE: u32 = 0xff_ff00ff;
+pub static SMART_GUIDE_TEXT_SIZE: f32 = 12.;
+
+/* Sidebearing handles */
+/// Size, in screen pixels, of the handles drawn where the sidebearings cross the baseline.
+pub static SIDEBEARING_HANDLE_SIZE: f32 = 8.;
+pub static SIDEBEARING_HANDLE_FILL: u32 = 0xff_3d7fe8;
+
+/* Kerning preview */
+pub static KERNING_PREVIEW_FILL: u32 = 0xff_555555;
+pub static KERNING_PREVIEW_CURRENT_FILL: u32 = 0xff_000000;
+/// The preview strip's baseline is this many UPM below the descender.
+pub static KERNING_PREVIEW_GAP: f32 = 200.;
+pub static KERNING_PREVIEW_DEFAULT_TEXT: &str = "nn/?nnoo/?oo";
+
+/* Autotrace */
+/// Traced shapes smaller than this many square pixels are specks, and are dropped.
+pub static TRACE_MIN_AREA: f32 = 3.;
+
+/* Image import */
+/// SVGs placed as images are rasterized so their longest side is this many pixels.
+pub static SVG_IMAGE_RASTER_SIZE: f32 = 2048.;
+
+/* Curvature comb */
+pub static COMB_TEETH_STROKE: u32 = 0x60_e0457b;
+pub static COMB_OUTLINE_STROKE: u32 = 0xc0_e0457b;
+pub static COMB_DISCONTINUITY_STROKE: u32 = 0xff_ff8c00;
+pub static COMB_INFLECTION_FILL: u32 = 0xff_2a9d8f;
+/// Radius, in screen pixels, of the marks at curvature discontinuities.
+pub static COMB_MARK_SIZE: f32 = 6.;
+/// Joins whose tangents differ by more than this many degrees are corners, and aren't marked.
+pub static COMB_G1_ANGLE_TOLERANCE: f32 = 1.;
+/// How much, relative to the larger of the two, the curvature may jump at a join before the
+/// join is marked.
+pub static COMB_G2_TOLERANCE: f32 = 0.05;
+/// Curvature below this is taken to be flat, so straight lines aren't marked.
+pub static COMB_FLAT_CURVATURE: f64 = 1e-5;
+
+/* Harmonize */
+/// Curves whose far handle is closer than this many units to the tangent are taken to be straight,
+/// and their points aren't harmonized.
+pub static HARMONIZE_MIN_DISTANCE: f64 = 0.01;
+
+/* Nib stroke */
+/// How many places along each curve the nib is put down at.
+pub static NIB_SAMPLES_PER_CURVE: usize = 32;
+/// Sides of the polygon an elliptical nib is drawn as.
+pub static NIB_ELLIPSE_SIDES: usize = 32;
+/// Turns sharper than this many degrees in a stroke's outline are kept as corners when it's fit
+/// with curves.
+pub static NIB_CORNER_ANGLE: f64 = 30.;
+/// How far, in units, the curves fit to a stroke's outline may stray from it.
+pub static NIB_FIT_ERROR: f64 = 0.5;
+pub static NIB_STROKE: u32 = 0xa0_2a9d8f;
+
+/* Variable width stroke */
+/// Pieces each curve is split into to make its widths change smoothly.
+pub static VWS_SMOOTH_STEPS: usize = 8;
+/// How far from the end, as a fraction of a curve, a constant width steps to the next one.
+pub static VWS_CONSTANT_STEP: f64 = 0.001;
+
 /// TODO: Deprecate this hack.
 /// See https://github.com/emilk/egui/issues/2639.
 #[rustfmt::skip]
@@ -1,10 +1,33 @@
+// Dashes are drawn by MFEKmath, which always starts the pattern where the contour starts. To start
+// it elsewhere along the pattern (a phase), or to stretch it so it divides the contour evenly, the
+// dashes' centerlines are cut out here instead and each is stroked by MFEKmath as a single dash.
+// glifparser's DashContour can't hold either option, so they're kept in the lib (see
+// editor::operation_stack).
+
 use glifparser::glif::contour::MFEKContourCommon;
 use glifparser::glif::{contour_operations::dash::DashContour, MFEKContour, MFEKOutline};
-use glifparser::{Glif, MFEKPointData};
+use glifparser::outline::skia::{FromSkiaPath as _, ToSkiaPaths as _};
+use glifparser::{Glif, MFEKPointData, Outline};
+use skia_safe::{path::AddPathMode, ContourMeasureIter};
 use MFEKmath::mfek::ResolveCubic;
 
 use super::ContourOperationBuild;
 
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DashOptions {
+    /// How far into the pattern the contour starts.
+    pub phase: f32,
+    /// Stretches or shrinks the pattern so a whole number of repeats fits the contour, so closed
+    /// contours end on a full gap instead of a cut dash.
+    pub fit_to_length: bool,
+}
+
+impl DashOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 impl ContourOperationBuild for DashContour {
     fn build(&self, contour: &MFEKContour<MFEKPointData>) -> MFEKOutline<MFEKPointData> {
         let mut glif = Glif::default();
@@ -23,3 +46,99 @@ impl ContourOperationBuild for DashContour {
         output
     }
 }
+
+/// Where the dashes of a pattern lie along a contour of the given length, as (start, end) distances.
+/// On closed contours a dash may run over the contour's start, in which case its start is after its
+/// end.
+pub fn dash_intervals(length: f64, dashes: &[f32], options: &DashOptions, closed: bool) -> Vec<(f64, f64)> {
+    let total: f64 = dashes.iter().map(|d| *d as f64).sum();
+    if dashes.is_empty() || total <= 0. || length <= 0. {
+        return vec![];
+    }
+    let scale = if options.fit_to_length {
+        length / ((length / total).round().max(1.) * total)
+    } else {
+        1.
+    };
+    let total = total * scale;
+
+    let mut ret = vec![];
+    let mut at = -(options.phase as f64 * scale).rem_euclid(total);
+    let mut i = 0;
+    while at < length {
+        let len = dashes[i % dashes.len()] as f64 * scale;
+        if i % 2 == 0 {
+            let (start, end) = (at.max(0.), (at + len).min(length));
+            if end > start {
+                ret.push((start, end));
+            }
+        }
+        at += len;
+        i += 1;
+    }
+
+    // A dash cut by the start of a closed contour is really one dash.
+    if closed && ret.len() > 1 && ret[0].0 <= 0. && ret[ret.len() - 1].1 >= length {
+        let (start, _) = ret.pop().unwrap();
+        ret[0].0 = start;
+    }
+    ret
+}
+
+/// Dashes a contour with a phase or fit to its length.
+pub fn build_dashes(
+    data: &DashContour,
+    options: &DashOptions,
+    contour: &MFEKContour<MFEKPointData>,
+) -> MFEKOutline<MFEKPointData> {
+    let closed = !contour.is_open();
+    let path = vec![contour.clone()].to_skia_paths(None).combined();
+    let measure = match ContourMeasureIter::from_path(&path, false, None).next() {
+        Some(measure) => measure,
+        None => return vec![],
+    };
+    let length = measure.length() as f64;
+
+    let mut ret = vec![];
+    for (start, end) in dash_intervals(length, &data.dash_desc, options, closed) {
+        let centerline = if start > end {
+            measure.segment(start as f32, length as f32, true).map(|mut path| {
+                if let Some(rest) = measure.segment(0., end as f32, true) {
+                    path.add_path(&rest, (0., 0.), AddPathMode::Extend);
+                }
+                path
+            })
+        } else {
+            measure.segment(start as f32, end as f32, true)
+        };
+        let centerline = match centerline {
+            Some(centerline) => centerline,
+            None => continue,
+        };
+        // One dash longer than the centerline, so MFEKmath strokes all of it.
+        let dash_length = if start > end { length - start + end } else { end - start };
+        let single = DashContour {
+            dash_desc: vec![dash_length as f32 + 1., 1.],
+            ..data.clone()
+        };
+        let pieces: Outline<MFEKPointData> = Outline::from_skia_path(&centerline);
+        for piece in pieces {
+            ret.extend(single.build(&piece.into()));
+        }
+    }
+    ret
+}
+
+#[test]
+fn dash_intervals_test() {
+    let options = DashOptions { phase: 5., fit_to_length: false };
+    assert_eq!(
+        dash_intervals(40., &[10., 10.], &options, false),
+        vec![(0., 5.), (15., 25.), (35., 40.)]
+    );
+    // The dash cut by the start of a closed contour is joined back up.
+    assert_eq!(dash_intervals(40., &[10., 10.], &options, true), vec![(35., 5.), (15., 25.)]);
+    // Fit, two repeats of 8 + 8 are shrunk to 30.
+    let options = DashOptions { phase: 0., fit_to_length: true };
+    assert_eq!(dash_intervals(30., &[8., 8.], &options, true), vec![(0., 7.5), (15., 22.5)]);
+}
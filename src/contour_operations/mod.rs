@@ -1,5 +1,7 @@
 pub mod dashalongpath;
+pub mod nibstroke;
 pub mod patternalongpath;
+pub mod stack;
 pub mod variablewidthstroke;
 
 use glifparser::glif::contour_operations::{unknown_op_outline, ContourOperations};
@@ -0,0 +1,290 @@
+// A broad nib, elliptical or rectangular, swept along a contour without turning with it, the way
+// a pen held at a constant angle draws (translation-nib expansion). The angle may instead be given
+// per point, in which case it turns evenly from each point to the next. The stroke is the union of
+// the hulls of the nib at successive places along the curves, which is then fit with cubics.
+//
+// glifparser's ContourOperations can't hold it, so it's only ever a stacked operation (see
+// contour_operations::stack).
+
+use flo_curves::bezier::fit_curve_cubic;
+use glifparser::glif::contour::MFEKContourCommon as _;
+use glifparser::glif::{MFEKContour, MFEKOutline};
+use glifparser::outline::skia::FromSkiaPath as _;
+use glifparser::{MFEKPointData, Outline};
+use skia_safe::Path;
+use MFEKmath::mfek::ResolveCubic as _;
+use MFEKmath::{vec2, Bezier, Evaluate as _, Piecewise, Vector};
+
+use super::ContourOperationBuild;
+use crate::constants::{
+    NIB_CORNER_ANGLE, NIB_ELLIPSE_SIDES, NIB_FIT_ERROR, NIB_SAMPLES_PER_CURVE,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NibShape {
+    Ellipse,
+    Rectangle,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NibContour {
+    pub shape: NibShape,
+    pub width: f32,
+    pub height: f32,
+    /// Degrees counter-clockwise of the nib's broad edge from the baseline.
+    pub angle: f32,
+    /// An angle per point, used instead of `angle` if given.
+    pub point_angles: Option<Vec<f32>>,
+}
+
+impl Default for NibContour {
+    fn default() -> Self {
+        Self {
+            shape: NibShape::Ellipse,
+            width: 60.,
+            height: 10.,
+            angle: 30.,
+            point_angles: None,
+        }
+    }
+}
+
+impl NibContour {
+    pub fn angle_at(&self, pi: usize) -> f32 {
+        self.point_angles
+            .as_ref()
+            .and_then(|angles| angles.get(pi).or_else(|| angles.last()))
+            .copied()
+            .unwrap_or(self.angle)
+    }
+
+    /// Gives the nib an angle per point of a contour of len points, repeating the last angle for
+    /// points there wasn't one for.
+    pub fn fit(&self, len: usize) -> Self {
+        let mut ret = self.clone();
+        if let Some(angles) = ret.point_angles.as_mut() {
+            let last = angles.last().copied().unwrap_or(self.angle);
+            angles.resize(len, last);
+        }
+        ret
+    }
+
+    /// The nib's outline put down at a place.
+    pub fn polygon(&self, at: Vector, angle: f32) -> Vec<Vector> {
+        let (rx, ry) = (self.width as f64 / 2., self.height as f64 / 2.);
+        let corners: Vec<(f64, f64)> = match self.shape {
+            NibShape::Ellipse => (0..NIB_ELLIPSE_SIDES)
+                .map(|side| {
+                    let theta = side as f64 / NIB_ELLIPSE_SIDES as f64 * std::f64::consts::TAU;
+                    (rx * theta.cos(), ry * theta.sin())
+                })
+                .collect(),
+            NibShape::Rectangle => vec![(-rx, -ry), (rx, -ry), (rx, ry), (-rx, ry)],
+        };
+        let (sin, cos) = (angle as f64).to_radians().sin_cos();
+        corners
+            .into_iter()
+            .map(|(x, y)| vec2![at.x + x * cos - y * sin, at.y + x * sin + y * cos])
+            .collect()
+    }
+
+    pub fn nib_path(&self, at: Vector, angle: f32) -> Path {
+        let mut path = Path::new();
+        add_polygon(&mut path, &self.polygon(at, angle));
+        path
+    }
+
+    pub fn to_plist(&self) -> plist::Value {
+        let mut dict = plist::Dictionary::new();
+        let shape = match self.shape {
+            NibShape::Ellipse => "ellipse",
+            NibShape::Rectangle => "rectangle",
+        };
+        dict.insert("shape".to_string(), plist::Value::String(shape.to_string()));
+        dict.insert("width".to_string(), plist::Value::Real(self.width as f64));
+        dict.insert("height".to_string(), plist::Value::Real(self.height as f64));
+        dict.insert("angle".to_string(), plist::Value::Real(self.angle as f64));
+        if let Some(angles) = &self.point_angles {
+            dict.insert(
+                "pointAngles".to_string(),
+                plist::Value::Array(angles.iter().map(|a| plist::Value::Real(*a as f64)).collect()),
+            );
+        }
+        plist::Value::Dictionary(dict)
+    }
+
+    pub fn from_plist(dict: &plist::Dictionary) -> Option<Self> {
+        let real = |key: &str| dict.get(key).and_then(|v| v.as_real()).map(|v| v as f32);
+        let shape = match dict.get("shape")?.as_string()? {
+            "ellipse" => NibShape::Ellipse,
+            "rectangle" => NibShape::Rectangle,
+            _ => return None,
+        };
+        let point_angles = dict.get("pointAngles").and_then(|v| v.as_array()).map(|angles| {
+            angles
+                .iter()
+                .filter_map(|a| a.as_real())
+                .map(|a| a as f32)
+                .collect()
+        });
+        Some(Self {
+            shape,
+            width: real("width")?,
+            height: real("height")?,
+            angle: real("angle")?,
+            point_angles,
+        })
+    }
+}
+
+fn add_polygon(path: &mut Path, polygon: &[Vector]) {
+    if let Some((first, rest)) = polygon.split_first() {
+        path.move_to(first.to_skia_point());
+        for point in rest {
+            path.line_to(point.to_skia_point());
+        }
+        path.close();
+    }
+}
+
+/// Andrew's monotone chain. The hull comes out counter-clockwise, so that hulls all wind the same
+/// way and their union can be taken with a non-zero fill.
+fn convex_hull(mut points: Vec<Vector>) -> Vec<Vector> {
+    points.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Vector, a: Vector, b: Vector| (a - o).cross(b - o);
+    let mut hull: Vec<Vector> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &Vector>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for &p in iter {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0. {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // Each half ends where the other starts.
+        hull.pop();
+    }
+    hull
+}
+
+/// Fits one run of a stroke's outline, from one corner to the next, with cubics.
+fn fit_run(path: &mut Path, run: &[Vector]) {
+    if run.len() == 2 {
+        path.line_to(run[1].to_skia_point());
+        return;
+    }
+    let start_tangent = (run[1] - run[0]).normalize();
+    let end_tangent = (run[run.len() - 2] - run[run.len() - 1]).normalize();
+    let curves: Vec<Bezier> = fit_curve_cubic(run, &start_tangent, &end_tangent, NIB_FIT_ERROR);
+    for bez in curves {
+        path.cubic_to(bez.w2.to_skia_point(), bez.w3.to_skia_point(), bez.w4.to_skia_point());
+    }
+}
+
+/// Replaces a closed polygon with curves, keeping its corners.
+fn fit_polygon(path: &mut Path, points: &[Vector]) {
+    let n = points.len();
+    let corner_cos = NIB_CORNER_ANGLE.to_radians().cos();
+    let corners: Vec<usize> = (0..n)
+        .filter(|&i| {
+            let (before, after) = (points[i] - points[(i + n - 1) % n], points[(i + 1) % n] - points[i]);
+            before.normalize().dot(after.normalize()) < corner_cos
+        })
+        .collect();
+
+    if corners.is_empty() {
+        let mut run = points.to_vec();
+        run.push(points[0]);
+        let tangent = (points[1] - points[n - 1]).normalize();
+        path.move_to(points[0].to_skia_point());
+        let curves: Vec<Bezier> = fit_curve_cubic(&run, &tangent, &-tangent, NIB_FIT_ERROR);
+        for bez in curves {
+            path.cubic_to(bez.w2.to_skia_point(), bez.w3.to_skia_point(), bez.w4.to_skia_point());
+        }
+    } else {
+        path.move_to(points[corners[0]].to_skia_point());
+        for (c, &start) in corners.iter().enumerate() {
+            let end = corners[(c + 1) % corners.len()];
+            let mut run = vec![points[start]];
+            let mut i = start;
+            loop {
+                i = (i + 1) % n;
+                run.push(points[i]);
+                if i == end {
+                    break;
+                }
+            }
+            fit_run(path, &run);
+        }
+    }
+    path.close();
+}
+
+impl ContourOperationBuild for NibContour {
+    fn build(&self, contour: &MFEKContour<MFEKPointData>) -> MFEKOutline<MFEKPointData> {
+        let len = contour.len();
+        let piecewise: Piecewise<Bezier> = contour.to_cubic().into();
+
+        let mut sweep = Path::new();
+        for (k, bez) in piecewise.segs.iter().enumerate() {
+            let (from, to) = (self.angle_at(k), self.angle_at((k + 1) % len));
+            let mut last: Option<Vec<Vector>> = None;
+            for sample in 0..=NIB_SAMPLES_PER_CURVE {
+                let t = sample as f64 / NIB_SAMPLES_PER_CURVE as f64;
+                let nib = self.polygon(bez.at(t), from + (to - from) * t as f32);
+                if let Some(last) = last {
+                    add_polygon(&mut sweep, &convex_hull(last.into_iter().chain(nib.iter().copied()).collect()));
+                }
+                last = Some(nib);
+            }
+        }
+
+        let sweep = match sweep.simplify() {
+            Some(sweep) => sweep,
+            None => {
+                log::warn!("Failed to find the outline of a nib stroke.");
+                return vec![contour.clone()];
+            }
+        };
+
+        let mut fitted = Path::new();
+        let polygons: Outline<MFEKPointData> = Outline::from_skia_path(&sweep);
+        for polygon in polygons {
+            let mut points: Vec<Vector> = vec![];
+            for point in polygon.iter().map(|p| vec2![p.x, p.y]) {
+                if points.last().map(|last: &Vector| (point - *last).magnitude() > 1e-3).unwrap_or(true) {
+                    points.push(point);
+                }
+            }
+            while points.len() > 1 && (points[0] - points[points.len() - 1]).magnitude() <= 1e-3 {
+                points.pop();
+            }
+            if points.len() >= 3 {
+                fit_polygon(&mut fitted, &points);
+            }
+        }
+
+        let outline: Outline<MFEKPointData> = Outline::from_skia_path(&fitted);
+        outline.iter().map(|c| c.into()).collect()
+    }
+}
+
+#[test]
+fn convex_hull_test() {
+    let hull = convex_hull(vec![
+        vec2![0., 0.],
+        vec2![10., 0.],
+        vec2![5., 5.],
+        vec2![10., 10.],
+        vec2![0., 10.],
+    ]);
+    assert_eq!(hull, vec![vec2![0., 0.], vec2![10., 0.], vec2![10., 10.], vec2![0., 10.]]);
+}
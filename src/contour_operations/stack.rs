@@ -0,0 +1,92 @@
+// A contour's operations can be stacked, each one applied to every contour the one before it
+// output, e.g. a dash and then a variable width stroke of each dash. The first operation is the
+// contour's own, which the tools edit, unless it's one glifparser can't hold, like a nib stroke;
+// the rest are kept in the glyph's lib (see editor::operation_stack).
+
+use glifparser::glif::contour_operations::ContourOperations;
+use glifparser::glif::{contour::MFEKContourCommon as _, MFEKContour, MFEKOutline};
+use glifparser::MFEKPointData;
+
+use super::nibstroke::NibContour;
+use super::ContourOperationBuild as _;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StageOperation {
+    Contour(ContourOperations<MFEKPointData>),
+    Nib(NibContour),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    pub operation: StageOperation,
+    pub enabled: bool,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self.operation {
+            StageOperation::Contour(ContourOperations::VariableWidthStroke { .. }) => "Variable width stroke",
+            StageOperation::Contour(ContourOperations::PatternAlongPath { .. }) => "Pattern along path",
+            StageOperation::Contour(ContourOperations::DashAlongPath { .. }) => "Dash along path",
+            StageOperation::Nib(_) => "Nib stroke",
+            #[allow(unreachable_patterns)]
+            _ => "Unknown operation",
+        }
+    }
+}
+
+/// Operations that were set up for one contour may not fit the contours an earlier stage makes
+/// out of it. Variable width strokes have a width per point, so the last width is repeated for
+/// points the original contour didn't have. (Nibs with an angle per point are fit the same way.)
+fn fit_operation(
+    operation: &ContourOperations<MFEKPointData>,
+    contour: &MFEKContour<MFEKPointData>,
+) -> ContourOperations<MFEKPointData> {
+    match operation {
+        ContourOperations::VariableWidthStroke { data } => {
+            let mut data = data.clone();
+            let wanted = contour.len() + 1;
+            if let Some(last) = data.handles.last().cloned() {
+                data.handles.resize(wanted, last);
+            }
+            ContourOperations::VariableWidthStroke { data }
+        }
+        operation => operation.clone(),
+    }
+}
+
+/// Applies the enabled stages, at most through stage `through` if given, to a contour.
+pub fn build_stack(
+    contour: &MFEKContour<MFEKPointData>,
+    stages: &[Stage],
+    through: Option<usize>,
+) -> MFEKOutline<MFEKPointData> {
+    let mut bare = contour.clone();
+    bare.set_operation(None);
+    let mut outline: MFEKOutline<MFEKPointData> = vec![bare];
+
+    let last = through.unwrap_or(usize::MAX);
+    for stage in stages.iter().take(last.saturating_add(1)).filter(|s| s.enabled) {
+        outline = outline
+            .into_iter()
+            .flat_map(|mut c| {
+                if c.len() <= 1 {
+                    return vec![c];
+                }
+                match &stage.operation {
+                    StageOperation::Contour(operation) => {
+                        let operation = Some(fit_operation(operation, &c));
+                        c.set_operation(operation.clone());
+                        operation.build(&c)
+                    }
+                    StageOperation::Nib(nib) => nib.fit(c.len()).build(&c),
+                }
+            })
+            .collect();
+    }
+
+    for contour in outline.iter_mut() {
+        contour.set_operation(None);
+    }
+    outline
+}
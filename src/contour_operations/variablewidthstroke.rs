@@ -1,10 +1,13 @@
-use glifparser::glif::contour_operations::vws::VWSContour;
+use glifparser::glif::contour::MFEKContourCommon as _;
+use glifparser::glif::contour_operations::vws::{InterpolationType, VWSContour, VWSHandle};
 use glifparser::glif::{MFEKContour, MFEKOutline};
 use glifparser::MFEKPointData;
+use serde_json::{json, Value};
 use MFEKmath::mfek::ResolveCubic;
-use MFEKmath::{variable_width_stroke, Piecewise, VWSSettings};
+use MFEKmath::{variable_width_stroke, Bezier, Piecewise, VWSSettings};
 
 use super::ContourOperationBuild;
+use crate::constants::{VWS_CONSTANT_STEP, VWS_SMOOTH_STEPS};
 
 impl ContourOperationBuild for VWSContour {
     fn build(&self, contour: &MFEKContour<MFEKPointData>) -> MFEKOutline<MFEKPointData> {
@@ -25,3 +28,203 @@ impl ContourOperationBuild for VWSContour {
         output
     }
 }
+
+/// How a variable width stroke's widths change from one point to the next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WidthInterpolation {
+    Linear,
+    /// Eases out of one width and into the next.
+    Smooth,
+    /// Keeps a point's width up to the next point.
+    Constant,
+}
+
+impl Default for WidthInterpolation {
+    fn default() -> Self {
+        WidthInterpolation::Linear
+    }
+}
+
+impl WidthInterpolation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WidthInterpolation::Linear => "linear",
+            WidthInterpolation::Smooth => "smooth",
+            WidthInterpolation::Constant => "constant",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(WidthInterpolation::Linear),
+            "smooth" => Some(WidthInterpolation::Smooth),
+            "constant" => Some(WidthInterpolation::Constant),
+            _ => None,
+        }
+    }
+
+    /// How far, from 0 to 1, from one width to the next the width is at t.
+    fn ease(&self, t: f64) -> f64 {
+        match self {
+            WidthInterpolation::Linear => t,
+            WidthInterpolation::Smooth => t * t * (3. - 2. * t),
+            WidthInterpolation::Constant => 0.,
+        }
+    }
+
+    /// Where a curve is split so that linearly interpolating between the splits follows this.
+    fn splits(&self) -> Vec<f64> {
+        match self {
+            WidthInterpolation::Linear => vec![],
+            WidthInterpolation::Smooth => (1..VWS_SMOOTH_STEPS)
+                .map(|step| step as f64 / VWS_SMOOTH_STEPS as f64)
+                .collect(),
+            WidthInterpolation::Constant => vec![1. - VWS_CONSTANT_STEP],
+        }
+    }
+}
+
+/// MFEKmath only interpolates widths linearly, so for the other kinds of interpolation the curves
+/// are split, and widths are given to the splits such that the stroke follows the wanted curve.
+/// interpolation gives, for each curve, how widths change along it; curves without one are linear.
+pub fn interpolate_widths(
+    contour: &MFEKContour<MFEKPointData>,
+    vws: &VWSContour,
+    interpolation: &[WidthInterpolation],
+) -> (MFEKContour<MFEKPointData>, VWSContour) {
+    let last_handle = match vws.handles.last() {
+        Some(last) => *last,
+        None => return (contour.clone(), vws.clone()),
+    };
+    if interpolation.iter().all(|i| *i == WidthInterpolation::Linear) {
+        return (contour.clone(), vws.clone());
+    }
+
+    let handle = |hi: usize| vws.handles.get(hi).copied().unwrap_or(last_handle);
+    let piecewise = Piecewise::from(contour.to_cubic());
+    let mut beziers: Vec<Bezier> = vec![];
+    let mut handles = vec![];
+    for (k, bez) in piecewise.segs.iter().enumerate() {
+        let (from, to) = (handle(k), handle(k + 1));
+        let mode = interpolation.get(k).copied().unwrap_or_default();
+        let splits = mode.splits();
+
+        handles.push(from);
+        for &t in &splits {
+            let e = mode.ease(t);
+            handles.push(VWSHandle {
+                left_offset: from.left_offset + (to.left_offset - from.left_offset) * e,
+                right_offset: from.right_offset + (to.right_offset - from.right_offset) * e,
+                interpolation: InterpolationType::Linear,
+                tangent_offset: 0.,
+            });
+        }
+        if splits.is_empty() {
+            beziers.push(bez.clone());
+        } else {
+            beziers.extend(bez.split_at_multiple_t(splits));
+        }
+    }
+    // The handles after the last curve: the closing one, or the end of an open contour.
+    handles.extend((piecewise.segs.len()..vws.handles.len()).map(handle));
+
+    let mut split: MFEKContour<MFEKPointData> = Piecewise::new(beziers, None).to_contour().into();
+    if contour.is_closed() {
+        split.set_closed();
+        if let Some(points) = split.cubic_mut() {
+            let (first, last) = (points.first().cloned(), points.last().cloned());
+            if let (Some(first), Some(last)) = (first, last) {
+                if points.len() > 1 && first.x == last.x && first.y == last.y {
+                    points.pop();
+                    points[0].b = last.b;
+                }
+            }
+        }
+    } else {
+        split.set_open();
+    }
+
+    let mut data = vws.clone();
+    data.handles = handles;
+    (split, data)
+}
+
+/// A contour's widths, to copy from one contour to others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WidthProfile {
+    pub handles: Vec<VWSHandle>,
+    pub interpolation: Vec<WidthInterpolation>,
+}
+
+impl WidthProfile {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "handles": self.handles,
+            "interpolation": self.interpolation.iter().map(|i| i.name()).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(v: &Value) -> Result<Self, String> {
+        let handles: Vec<VWSHandle> =
+            serde_json::from_value(v["handles"].clone()).map_err(|e| e.to_string())?;
+        if handles.is_empty() {
+            return Err("The width profile has no widths.".to_string());
+        }
+        let interpolation = v["interpolation"]
+            .as_array()
+            .map(|modes| {
+                modes
+                    .iter()
+                    .map(|m| m.as_str().and_then(WidthInterpolation::from_name).unwrap_or_default())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Self { handles, interpolation })
+    }
+
+    /// The profile stretched or squeezed to a contour of points points, each width taken from as
+    /// far along the profile as the point is along the contour. There's one handle more than there
+    /// are points, as in a VWSContour.
+    pub fn resample(&self, points: usize) -> Self {
+        // The last handle only matters to closed contours, where it's the same as the first.
+        let source = &self.handles[..self.handles.len().saturating_sub(1).max(1)];
+        let at = |i: usize| -> (VWSHandle, WidthInterpolation) {
+            let pos = if points > 1 {
+                i as f64 / (points - 1) as f64 * (source.len() - 1) as f64
+            } else {
+                0.
+            };
+            let (lo, frac) = (pos.floor() as usize, pos.fract());
+            let (a, b) = (source[lo], source[(lo + 1).min(source.len() - 1)]);
+            let handle = VWSHandle {
+                left_offset: a.left_offset + (b.left_offset - a.left_offset) * frac,
+                right_offset: a.right_offset + (b.right_offset - a.right_offset) * frac,
+                tangent_offset: if frac == 0. { a.tangent_offset } else { 0. },
+                interpolation: a.interpolation,
+            };
+            (handle, self.interpolation.get(lo).copied().unwrap_or_default())
+        };
+
+        let (mut handles, interpolation): (Vec<_>, Vec<_>) = (0..points).map(at).unzip();
+        handles.push(handles.first().copied().unwrap_or(source[0]));
+        Self { handles, interpolation }
+    }
+}
+
+#[test]
+fn width_profile_resample_test() {
+    let handle = |w: f64| VWSHandle {
+        left_offset: w,
+        right_offset: w,
+        interpolation: InterpolationType::Linear,
+        tangent_offset: 0.,
+    };
+    let profile = WidthProfile {
+        handles: vec![handle(10.), handle(30.), handle(10.)],
+        interpolation: vec![WidthInterpolation::Smooth],
+    };
+    let resampled = profile.resample(3);
+    let widths: Vec<f64> = resampled.handles.iter().map(|h| h.left_offset).collect();
+    assert_eq!(widths, vec![10., 20., 30., 10.]);
+    assert_eq!(resampled.interpolation[0], WidthInterpolation::Smooth);
+}
@@ -0,0 +1,445 @@
+// Traces a placed image into contours. The image is thresholded into a bitmap, the boundaries
+// between black and white pixels are followed into closed polygons, corners are found on those, and
+// the runs between corners are fitted with cubic Béziers (Schneider, "An Algorithm for
+// Automatically Fitting Digitized Curves", Graphics Gems, 1990).
+
+use glifparser::glif::MFEKContour;
+use glifparser::matrix::ToSkiaMatrix;
+use glifparser::outline::skia::FromSkiaPath as _;
+use glifparser::{MFEKPointData, Outline};
+use kurbo::Vec2;
+use skia_safe::{self as skia, Matrix, Path};
+
+use super::Editor;
+use crate::constants::TRACE_MIN_AREA;
+
+use std::collections::BTreeMap;
+
+/// How many samples to either side of a point are looked at to decide if it's a corner.
+const CORNER_SPAN: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TraceSettings {
+    /// Pixels darker than this are inside the outline.
+    pub threshold: u8,
+    /// Places where the outline turns sharper than this, in degrees, become corner points.
+    pub corner_angle: f32,
+    /// How far, in image pixels, the fitted curves may stray from the pixels' edges.
+    pub tolerance: f32,
+}
+
+impl Default for TraceSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 128,
+            corner_angle: 135.,
+            tolerance: 1.,
+        }
+    }
+}
+
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    bits: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Thresholds premultiplied RGBA pixels, as if the image were drawn over white.
+    pub fn from_rgba(data: &[u8], width: usize, height: usize, threshold: u8) -> Self {
+        let bits = data
+            .chunks_exact(4)
+            .take(width * height)
+            .map(|p| {
+                let (r, g, b, a) = (p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32);
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b + (255. - a);
+                luma < threshold as f32
+            })
+            .collect();
+        Self { width, height, bits }
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.bits[y as usize * self.width + x as usize]
+    }
+}
+
+/// Follows the edges between black and white pixels into closed polygons of pixel corners. Outer
+/// boundaries come out with a positive signed area, holes with a negative one.
+fn trace_polygons(bitmap: &Bitmap) -> Vec<Vec<(i64, i64)>> {
+    // Every edge keeps black on its right, as seen with y going down.
+    let mut edges: BTreeMap<(i64, i64), Vec<(i64, i64)>> = BTreeMap::new();
+    let mut add_edge = |from, to| edges.entry(from).or_insert_with(Vec::new).push(to);
+    for y in 0..bitmap.height as i64 {
+        for x in 0..bitmap.width as i64 {
+            if !bitmap.get(x, y) {
+                continue;
+            }
+            if !bitmap.get(x, y - 1) {
+                add_edge((x, y), (x + 1, y));
+            }
+            if !bitmap.get(x + 1, y) {
+                add_edge((x + 1, y), (x + 1, y + 1));
+            }
+            if !bitmap.get(x, y + 1) {
+                add_edge((x + 1, y + 1), (x, y + 1));
+            }
+            if !bitmap.get(x - 1, y) {
+                add_edge((x, y + 1), (x, y));
+            }
+        }
+    }
+
+    let mut polygons = vec![];
+    while let Some((&start, _)) = edges.first_key_value() {
+        let mut polygon = vec![start];
+        let mut direction = (0, 0);
+        let mut at = start;
+        loop {
+            let outgoing = edges.get_mut(&at).unwrap();
+            // Where two black pixels touch diagonally there are two ways out; we always take the
+            // right turn, which keeps such pixels apart.
+            let right = (-direction.1, direction.0);
+            let idx = outgoing
+                .iter()
+                .position(|to| (to.0 - at.0, to.1 - at.1) == right)
+                .unwrap_or(0);
+            let to = outgoing.remove(idx);
+            if outgoing.is_empty() {
+                edges.remove(&at);
+            }
+            direction = (to.0 - at.0, to.1 - at.1);
+            at = to;
+            if at == start {
+                break;
+            }
+            polygon.push(at);
+        }
+        polygons.push(polygon);
+    }
+    polygons
+}
+
+fn signed_area(polygon: &[(i64, i64)]) -> i64 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<i64>()
+        / 2
+}
+
+fn normalized(v: Vec2) -> Vec2 {
+    let len = v.hypot();
+    if len == 0. {
+        v
+    } else {
+        v / len
+    }
+}
+
+fn angle_at(samples: &[Vec2], i: usize, span: usize) -> f64 {
+    let n = samples.len();
+    let a = samples[(i + n - span) % n] - samples[i];
+    let b = samples[(i + span) % n] - samples[i];
+    let cos = a.dot(b) / (a.hypot() * b.hypot());
+    cos.clamp(-1., 1.).acos().to_degrees()
+}
+
+fn find_corners(samples: &[Vec2], corner_angle: f64) -> Vec<usize> {
+    let n = samples.len();
+    if n <= CORNER_SPAN * 2 + 2 {
+        return vec![];
+    }
+    let angles: Vec<f64> = (0..n).map(|i| angle_at(samples, i, CORNER_SPAN)).collect();
+    // A sharp turn looks sharp from several samples in a row; keep only the sharpest of them.
+    (0..n)
+        .filter(|&i| angles[i] < corner_angle)
+        .filter(|&i| {
+            (1..=CORNER_SPAN).all(|d| {
+                let (before, after) = (angles[(i + n - d) % n], angles[(i + d) % n]);
+                before >= angles[i] && after > angles[i]
+            })
+        })
+        .collect()
+}
+
+type Cubic = [Vec2; 4];
+
+fn bezier_point(bez: &Cubic, t: f64) -> Vec2 {
+    let mt = 1. - t;
+    bez[0] * (mt * mt * mt) + bez[1] * (3. * mt * mt * t) + bez[2] * (3. * mt * t * t) + bez[3] * (t * t * t)
+}
+
+fn bezier_derivative(bez: &Cubic, t: f64) -> Vec2 {
+    let mt = 1. - t;
+    (bez[1] - bez[0]) * (3. * mt * mt) + (bez[2] - bez[1]) * (6. * mt * t) + (bez[3] - bez[2]) * (3. * t * t)
+}
+
+fn bezier_second_derivative(bez: &Cubic, t: f64) -> Vec2 {
+    (bez[2] - bez[1] * 2. + bez[0]) * (6. * (1. - t)) + (bez[3] - bez[2] * 2. + bez[1]) * (6. * t)
+}
+
+fn chord_length_parameters(points: &[Vec2]) -> Vec<f64> {
+    let mut u = vec![0.];
+    for i in 1..points.len() {
+        u.push(u[i - 1] + (points[i] - points[i - 1]).hypot());
+    }
+    let total = *u.last().unwrap();
+    if total > 0. {
+        u.iter_mut().for_each(|u| *u /= total);
+    }
+    u
+}
+
+/// Least-squares fit of the handle lengths along the given tangents.
+fn generate_bezier(points: &[Vec2], u: &[f64], t1: Vec2, t2: Vec2) -> Cubic {
+    let (first, last) = (points[0], *points.last().unwrap());
+    let mut c = [[0.; 2]; 2];
+    let mut x = [0.; 2];
+    for (p, &t) in points.iter().zip(u) {
+        let mt = 1. - t;
+        let (b0, b1, b2, b3) = (mt * mt * mt, 3. * mt * mt * t, 3. * mt * t * t, t * t * t);
+        let (a1, a2) = (t1 * b1, t2 * b2);
+        c[0][0] += a1.dot(a1);
+        c[0][1] += a1.dot(a2);
+        c[1][1] += a2.dot(a2);
+        let tmp = *p - (first * (b0 + b1) + last * (b2 + b3));
+        x[0] += a1.dot(tmp);
+        x[1] += a2.dot(tmp);
+    }
+    c[1][0] = c[0][1];
+
+    let det_c0_c1 = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let det_c0_x = c[0][0] * x[1] - c[1][0] * x[0];
+    let det_x_c1 = x[0] * c[1][1] - x[1] * c[0][1];
+    let (mut alpha1, mut alpha2) = if det_c0_c1 == 0. {
+        (0., 0.)
+    } else {
+        (det_x_c1 / det_c0_c1, det_c0_x / det_c0_c1)
+    };
+
+    // Negative or tiny handles give loops and cusps, so fall back to the usual third of the chord.
+    let chord = (last - first).hypot();
+    if alpha1 < chord * 1e-6 || alpha2 < chord * 1e-6 {
+        alpha1 = chord / 3.;
+        alpha2 = chord / 3.;
+    }
+    [first, first + t1 * alpha1, last + t2 * alpha2, last]
+}
+
+fn max_error(points: &[Vec2], bez: &Cubic, u: &[f64]) -> (f64, usize) {
+    let mut ret = (0., points.len() / 2);
+    for i in 1..points.len() - 1 {
+        let dist = (bezier_point(bez, u[i]) - points[i]).hypot2();
+        if dist >= ret.0 {
+            ret = (dist, i);
+        }
+    }
+    ret
+}
+
+fn reparameterize(points: &[Vec2], u: &[f64], bez: &Cubic) -> Vec<f64> {
+    points
+        .iter()
+        .zip(u)
+        .map(|(p, &t)| {
+            let d = bezier_point(bez, t) - *p;
+            let d1 = bezier_derivative(bez, t);
+            let d2 = bezier_second_derivative(bez, t);
+            let denominator = d1.dot(d1) + d.dot(d2);
+            if denominator == 0. {
+                t
+            } else {
+                t - d.dot(d1) / denominator
+            }
+        })
+        .collect()
+}
+
+/// t1 points out of the start of the curve, t2 back out of its end.
+fn fit_cubic(points: &[Vec2], t1: Vec2, t2: Vec2, tolerance: f64, out: &mut Vec<Cubic>) {
+    let (first, last) = (points[0], *points.last().unwrap());
+    if points.len() == 2 {
+        let dist = (last - first).hypot() / 3.;
+        out.push([first, first + t1 * dist, last + t2 * dist, last]);
+        return;
+    }
+
+    let tolerance2 = tolerance * tolerance;
+    let mut u = chord_length_parameters(points);
+    let mut bez = generate_bezier(points, &u, t1, t2);
+    let (mut error, mut split) = max_error(points, &bez, &u);
+    if error < tolerance2 {
+        out.push(bez);
+        return;
+    }
+
+    // Close enough that moving the samples along the curve may be all it takes.
+    if error < tolerance2 * 4. {
+        for _ in 0..4 {
+            u = reparameterize(points, &u, &bez);
+            bez = generate_bezier(points, &u, t1, t2);
+            (error, split) = max_error(points, &bez, &u);
+            if error < tolerance2 {
+                out.push(bez);
+                return;
+            }
+        }
+    }
+
+    let center = normalized(points[split - 1] - points[split + 1]);
+    fit_cubic(&points[..=split], t1, center, tolerance, out);
+    fit_cubic(&points[split..], center * -1., t2, tolerance, out);
+}
+
+/// Fits one closed polygon of pixel corners with cubic Béziers.
+fn fit_polygon(polygon: &[(i64, i64)], settings: &TraceSettings) -> Vec<Cubic> {
+    let n = polygon.len();
+    // The midpoints of the pixel edges follow the shape better than the staircase of corners.
+    let mut samples: Vec<Vec2> = (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            Vec2::new((a.0 + b.0) as f64 / 2., (a.1 + b.1) as f64 / 2.)
+        })
+        .collect();
+
+    let corners = find_corners(&samples, settings.corner_angle as f64);
+    let smoothed: Vec<Vec2> = (0..n)
+        .map(|i| {
+            if corners.contains(&i) {
+                samples[i]
+            } else {
+                (samples[(i + n - 1) % n] + samples[i] * 2. + samples[(i + 1) % n]) / 4.
+            }
+        })
+        .collect();
+    samples = smoothed;
+
+    // A closed curve needs at least two pieces, so split smoothly where there's no corner.
+    let mut splits: Vec<(usize, bool)> = corners.iter().map(|&i| (i, true)).collect();
+    match splits.len() {
+        0 => splits = vec![(0, false), (n / 2, false)],
+        1 => splits.push(((splits[0].0 + n / 2) % n, false)),
+        _ => {}
+    }
+    splits.sort();
+
+    let smooth_tangent = |i: usize| normalized(samples[(i + 2) % n] - samples[(i + n - 2) % n]);
+
+    let mut ret = vec![];
+    for (j, &(start, start_corner)) in splits.iter().enumerate() {
+        let (end, end_corner) = splits[(j + 1) % splits.len()];
+        let len = (end + n - start) % n;
+        let points: Vec<Vec2> = (0..=len).map(|k| samples[(start + k) % n]).collect();
+        let reach = CORNER_SPAN.min(points.len() - 1);
+        let t1 = if start_corner {
+            normalized(points[reach] - points[0])
+        } else {
+            smooth_tangent(start)
+        };
+        let t2 = if end_corner {
+            normalized(points[points.len() - 1 - reach] - points[points.len() - 1])
+        } else {
+            smooth_tangent(end) * -1.
+        };
+        fit_cubic(&points, t1, t2, settings.tolerance as f64, &mut ret);
+    }
+    ret
+}
+
+/// Traces a bitmap into closed cubic curves, in pixel coordinates.
+pub fn trace_bitmap(bitmap: &Bitmap, settings: &TraceSettings) -> Vec<Vec<Cubic>> {
+    trace_polygons(bitmap)
+        .into_iter()
+        .filter(|polygon| signed_area(polygon).abs() as f32 >= TRACE_MIN_AREA)
+        .map(|polygon| fit_polygon(&polygon, settings))
+        .collect()
+}
+
+#[test]
+fn trace_bitmap_test() {
+    // A 6×6 square with a 2×2 hole.
+    let mut bits = vec![false; 10 * 10];
+    for y in 2..8 {
+        for x in 2..8 {
+            bits[y * 10 + x] = !(4..6).contains(&x) || !(4..6).contains(&y);
+        }
+    }
+    let bitmap = Bitmap { width: 10, height: 10, bits };
+    let polygons = trace_polygons(&bitmap);
+    assert_eq!(polygons.len(), 2);
+    let mut areas: Vec<_> = polygons.iter().map(Vec::as_slice).map(signed_area).collect();
+    areas.sort();
+    assert_eq!(areas, vec![-4, 36]);
+
+    let settings = TraceSettings::default();
+    let curves = trace_bitmap(&bitmap, &settings);
+    assert_eq!(curves.len(), 2);
+    for curve in curves {
+        assert!(curve.len() >= 2);
+        // Every curve must join up with the next.
+        for (a, b) in curve.iter().zip(curve.iter().cycle().skip(1)) {
+            assert!((a[3] - b[0]).hypot() < 1e-9);
+        }
+    }
+}
+
+impl Editor {
+    /// Traces image idx of the active layer and adds the result to the layer as new contours.
+    /// Returns how many contours were added. Must be called between begin_modification and
+    /// end_modification.
+    pub fn trace_image(&mut self, idx: usize, settings: &TraceSettings) -> Result<usize, String> {
+        let (l_image, i_matrix) = self
+            .get_active_layer_ref()
+            .images
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| "No such image on this layer.".to_string())?;
+        let image = self
+            .images
+            .get(&l_image.filename)
+            .ok_or_else(|| format!("Image {:?} isn't loaded.", l_image.filename))?;
+
+        let (width, height) = (image.img.width(), image.img.height());
+        let bitmap = Bitmap::from_rgba(&image.data, width as usize, height as usize, settings.threshold);
+        let curves = trace_bitmap(&bitmap, settings);
+        if curves.is_empty() {
+            return Err("Nothing in the image is darker than the threshold.".to_string());
+        }
+
+        // The same transform render_frame draws the image with, so the outlines land on it.
+        let matrix: Matrix = i_matrix.to_skia_matrix()
+            * skia::EncodedOrigin::to_matrix(skia::EncodedOrigin::BottomLeft, (width, height));
+        // Outer contours are traced with a positive area, which is what the PostScript convention
+        // wants in glyph space. A mirroring transform, which includes the usual flip from the
+        // image's y-down space, turns them around, so they have to be reversed back.
+        let mirrored = matrix.scale_x() * matrix.scale_y() - matrix.skew_x() * matrix.skew_y() < 0.;
+        let map = |p: Vec2| matrix.map_xy(p.x as f32, p.y as f32);
+
+        let mut path = Path::new();
+        for mut curve in curves {
+            if mirrored {
+                curve.reverse();
+                curve.iter_mut().for_each(|c| c.reverse());
+            }
+            path.move_to(map(curve[0][0]));
+            for c in curve {
+                path.cubic_to(map(c[1]), map(c[2]), map(c[3]));
+            }
+            path.close();
+        }
+
+        let outline: Outline<MFEKPointData> = Outline::from_skia_path(&path);
+        let contours: Vec<MFEKContour<MFEKPointData>> = outline.iter().map(|c| c.into()).collect();
+        let added = contours.len();
+        self.get_active_layer_mut().outline.extend(contours);
+        Ok(added)
+    }
+}
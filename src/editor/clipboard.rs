@@ -0,0 +1,342 @@
+// Copied outlines go on the system clipboard, so they can be pasted into the other glyphs being
+// edited, each of which is its own MFEKglif process. Our own format keeps everything: contour
+// operations and their stacks, point types and images, along with where the images are so they
+// can be found from another font. arboard only gives us plain text, so it's put in the lib of a
+// GLIF glyph holding the exported outline, which other font editors can paste as well. Outlines
+// can also be put on the clipboard as bare GLIF or SVG, and both can be pasted, so shapes can move
+// between MFEKglif and other font editors and vector apps. On paste the text is sniffed to tell
+// which format it's in.
+
+use glifparser::glif::{Layer, MFEKContour, MFEKGlif};
+use glifparser::{Glif, MFEKPointData};
+use kurbo::Affine;
+use serde_json::{json, Value};
+use xmltree::{Element, EmitterConfig, XMLNode};
+
+use super::images::ImageSettings;
+use super::svg;
+use super::Editor;
+use crate::contour_operations::variablewidthstroke::WidthProfile;
+use crate::user_interface::gui;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Our own format is JSON holding the glifjson of a layer, its contours' operation stacks, its
+/// images' paths, and each image's settings. It's kept under this key in the lib of the copied
+/// glyph.
+pub const MFEK_CLIPBOARD_LIB_KEY: &str = "org.mfek.clipboard";
+/// Bumped whenever the JSON changes in a way older versions can't read.
+pub const MFEK_CLIPBOARD_VERSION: u64 = 2;
+/// What older versions put on the clipboard: this, a tab, then the glifjson of a layer. It can still
+/// be pasted.
+pub const GLIFJSON_CLIPBOARD_MIMETYPE: &str = "text/vnd.mfek.glifjson";
+/// A variable width stroke's widths, copied to be given to other contours.
+pub const WIDTH_PROFILE_CLIPBOARD_MIMETYPE: &str = "application/vnd.mfek.width-profile+json";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClipboardFormat {
+    /// A GLIF glyph with our own format in its lib.
+    Mfek,
+    /// A GLIF <outline> element.
+    Glif,
+    /// An SVG document.
+    Svg,
+}
+
+#[derive(Debug)]
+pub struct ClipboardContents {
+    pub layer: Layer<MFEKPointData>,
+    /// For each of the layer's contours, the operation stack it was copied with, if any.
+    pub stacks: Vec<Option<plist::Value>>,
+    /// For each of the layer's images, by file name, where it was found.
+    pub images: HashMap<PathBuf, PathBuf>,
+    /// For each of the layer's images, how it was shown in the glyph it was copied from.
+    pub image_settings: Vec<ImageSettings>,
+}
+
+fn settings_to_json(settings: &ImageSettings) -> Value {
+    json!({
+        "opacity": settings.opacity,
+        "visible": settings.visible,
+        "locked": settings.locked,
+        "crop": settings.crop,
+    })
+}
+
+fn settings_from_json(v: &Value) -> ImageSettings {
+    let mut ret = ImageSettings::default();
+    if let Some(opacity) = v["opacity"].as_f64() {
+        ret.opacity = opacity as f32;
+    }
+    if let Some(visible) = v["visible"].as_bool() {
+        ret.visible = visible;
+    }
+    if let Some(locked) = v["locked"].as_bool() {
+        ret.locked = locked;
+    }
+    if let Some(crop) = v["crop"].as_array() {
+        for (c, v) in ret.crop.iter_mut().zip(crop) {
+            *c = v.as_f64().unwrap_or(0.) as f32;
+        }
+    }
+    ret
+}
+
+fn glif_outline(glif: &str) -> Result<Element, String> {
+    let root = Element::parse(glif.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(root
+        .get_child("outline")
+        .cloned()
+        .unwrap_or_else(|| Element::new("outline")))
+}
+
+fn element_text(element: &Element) -> Result<String, String> {
+    let mut ret = vec![];
+    element
+        .write_with_config(
+            &mut ret,
+            EmitterConfig::new()
+                .perform_indent(true)
+                .write_document_declaration(false),
+        )
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(ret).map_err(|e| e.to_string())
+}
+
+fn clipboard_glyph(outline: Element) -> Element {
+    let mut glyph = Element::new("glyph");
+    glyph.attributes.insert("name".to_string(), "clipboard".to_string());
+    glyph.attributes.insert("format".to_string(), "2".to_string());
+    glyph.children.push(XMLNode::Element(outline));
+    glyph
+}
+
+fn text_element(name: &str, text: &str) -> XMLNode {
+    let mut ret = Element::new(name);
+    ret.children.push(XMLNode::Text(text.to_string()));
+    XMLNode::Element(ret)
+}
+
+/// Our own format, if the text is a glyph we copied.
+fn glif_mfek_data(text: &str) -> Option<String> {
+    if !text.starts_with('<') {
+        return None;
+    }
+    let root = Element::parse(text.as_bytes()).ok()?;
+    let dict = root.get_child("lib")?.get_child("dict")?;
+    let mut children = dict.children.iter().filter_map(|c| c.as_element());
+    while let Some(key) = children.next() {
+        let value = children.next()?;
+        if key.name == "key" && key.get_text().as_deref() == Some(MFEK_CLIPBOARD_LIB_KEY) {
+            return value.get_text().map(|t| t.into_owned());
+        }
+    }
+    None
+}
+
+/// Whether clipboard text is in a format outlines are copied as, and not, say, a word or a URL.
+/// SVG path data doesn't count, as almost any text could be meant as it.
+pub fn looks_like_outlines(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with('<') || text.starts_with('{') || text.starts_with(GLIFJSON_CLIPBOARD_MIMETYPE)
+}
+
+/// Wraps bare <outline> elements, as other editors copy them, into a glyph glifparser can read.
+fn as_glif_glyph(text: &str) -> Result<String, String> {
+    let root = Element::parse(text.as_bytes()).map_err(|e| format!("Invalid GLIF: {}", e))?;
+    match root.name.as_str() {
+        "glyph" => Ok(text.to_string()),
+        "outline" => {
+            let mut ret = vec![];
+            clipboard_glyph(root).write(&mut ret).map_err(|e| e.to_string())?;
+            String::from_utf8(ret).map_err(|e| e.to_string())
+        }
+        name => Err(format!("Can't paste a <{}> element.", name)),
+    }
+}
+
+impl Editor {
+    /// The <outline> of a layer as it'd be exported.
+    fn exported_outline(&self, layer: &Layer<MFEKPointData>) -> Result<Element, String> {
+        let mut layer = layer.clone();
+        let glif: Glif<MFEKPointData> = self.with_glyph(|glyph| glyph.to_exported(&mut layer));
+        let xml = glifparser::write(&glif).map_err(|e| format!("{:?}", e))?;
+        glif_outline(&xml)
+    }
+
+    /// Text for the clipboard holding a layer, usually the copied part of the active one. Only our
+    /// own format keeps the contours' stacks (see copied_stack_entry) and the images' settings.
+    pub fn clipboard_text(
+        &self,
+        layer: &Layer<MFEKPointData>,
+        stacks: &[Option<plist::Value>],
+        image_settings: &[ImageSettings],
+        format: ClipboardFormat,
+    ) -> Result<String, String> {
+        match format {
+            ClipboardFormat::Mfek => {
+                let images: serde_json::Map<String, Value> = layer
+                    .images
+                    .iter()
+                    .map(|(image, _)| {
+                        (
+                            image.filename.to_string_lossy().into_owned(),
+                            json!({ "path": self.image_path(&image.filename) }),
+                        )
+                    })
+                    .collect();
+                let image_settings: Vec<Value> = image_settings.iter().map(settings_to_json).collect();
+                let contents = json!({
+                    "version": MFEK_CLIPBOARD_VERSION,
+                    "layer": layer,
+                    "stacks": stacks,
+                    "images": images,
+                    "imageSettings": image_settings,
+                });
+                let json = serde_json::to_string(&contents).map_err(|e| e.to_string())?;
+
+                let mut dict = Element::new("dict");
+                dict.children.push(text_element("key", MFEK_CLIPBOARD_LIB_KEY));
+                dict.children.push(text_element("string", &json));
+                let mut lib = Element::new("lib");
+                lib.children.push(XMLNode::Element(dict));
+                let mut glyph = clipboard_glyph(self.exported_outline(layer)?);
+                glyph.children.push(XMLNode::Element(lib));
+                element_text(&glyph)
+            }
+            ClipboardFormat::Glif => element_text(&self.exported_outline(layer)?),
+            ClipboardFormat::Svg => Ok(self.svg_document(&layer.outline)),
+        }
+    }
+
+    /// Reads clipboard text in any of the formats we understand: our own, glifjson of a layer or
+    /// of a whole glyph, GLIF (a glyph or just its outline), an SVG document or SVG path data.
+    pub fn clipboard_contents(&self, text: &str) -> Result<ClipboardContents, String> {
+        let mut layer = Layer {
+            name: "".to_string(),
+            visible: true,
+            color: None,
+            outline: vec![],
+            operation: None,
+            images: vec![],
+        };
+        let mut stacks = vec![];
+        let mut images = HashMap::new();
+        let mut image_settings = vec![];
+        let text = text.trim();
+        let mismatched = |e: serde_json::Error| {
+            format!("Could not understand clipboard contents we think MFEKglif produced. Mismatched MFEKglif versions running on same machine? {}", e)
+        };
+
+        let old_glifjson = text
+            .strip_prefix(GLIFJSON_CLIPBOARD_MIMETYPE)
+            .and_then(|data| data.strip_prefix('\t'));
+        if let Some(data) = glif_mfek_data(text) {
+            let mut contents: Value = serde_json::from_str(&data).map_err(mismatched)?;
+            let version = contents["version"].as_u64().unwrap_or(0);
+            if version > MFEK_CLIPBOARD_VERSION {
+                return Err(format!("The clipboard holds outlines from a newer MFEKglif (format version {}), which this one can't read.", version));
+            }
+            layer = serde_json::from_value(contents["layer"].take()).map_err(mismatched)?;
+            if let Some(copied) = contents["stacks"].as_array() {
+                stacks = copied
+                    .iter()
+                    .map(|stack| serde_json::from_value(stack.clone()).ok().flatten())
+                    .collect();
+            }
+            if let Some(copied) = contents["images"].as_object() {
+                for (filename, image) in copied {
+                    let path = image["path"].as_str().map(PathBuf::from).unwrap_or_default();
+                    images.insert(PathBuf::from(filename), path);
+                }
+            }
+            if let Some(copied) = contents["imageSettings"].as_array() {
+                image_settings = copied.iter().map(settings_from_json).collect();
+            }
+        } else if let Some(data) = old_glifjson {
+            layer = serde_json::from_str(data).map_err(mismatched)?;
+        } else {
+            layer.outline = self.foreign_clipboard_outline(text)?;
+        }
+
+        if layer.outline.is_empty() && layer.images.is_empty() {
+            return Err("The clipboard has no outlines.".to_string());
+        }
+        Ok(ClipboardContents {
+            layer,
+            stacks,
+            images,
+            image_settings,
+        })
+    }
+
+    /// Reads outlines copied by other programs.
+    fn foreign_clipboard_outline(&self, text: &str) -> Result<Vec<MFEKContour<MFEKPointData>>, String> {
+        let glyph: MFEKGlif<MFEKPointData> = if text.starts_with('{') {
+            serde_json::from_str(text).map_err(|e| format!("Invalid glifjson: {}", e))?
+        } else if text.contains("<svg") {
+            let (path, viewport) = svg::parse_svg(text.as_bytes())?;
+            return Ok(self.svg_contours(path, viewport, None));
+        } else if text.starts_with('<') {
+            let glif: Glif<MFEKPointData> = glifparser::read(&as_glif_glyph(text)?)
+                .map_err(|e| format!("Invalid GLIF: {:?}", e))?;
+            glif.into()
+        } else {
+            // Bare path data has no viewport to fit to the em, so it's taken to be in font units.
+            let path = svg::parse_svg_path_data(text)?;
+            return Ok(svg::svg_path_contours(path, Affine::scale_non_uniform(1., -1.)));
+        };
+        Ok(glyph.layers.into_iter().flat_map(|l| l.outline).collect())
+    }
+
+    /// Adds copied images to the active layer. Ones this glyph can't find where it looks, as
+    /// happens when they were copied from another font, are placed from where they were copied.
+    /// This function must be called between begin_modification and end_modification.
+    pub fn paste_images(&mut self, contents: &ClipboardContents) -> Result<(), String> {
+        let li = self.layer_idx.unwrap();
+        for (ii, (image, matrix)) in contents.layer.images.iter().enumerate() {
+            self.get_active_layer_mut().images.push((image.clone(), *matrix));
+            self.recache_images();
+            if !self.images.contains_key(&image.filename) {
+                self.get_active_layer_mut().images.pop();
+                let path = contents.images.get(&image.filename).ok_or_else(|| {
+                    format!("Couldn't find the copied image {}.", image.filename.display())
+                })?;
+                self.add_image_to_active_layer(path.clone())?;
+                let (_, new_matrix) = self.get_active_layer_mut().images.last_mut().unwrap();
+                *new_matrix = *matrix;
+            }
+            if let Some(settings) = contents.image_settings.get(ii) {
+                let placed = self.get_active_layer_ref().images.len() - 1;
+                self.set_image_settings(li, placed, *settings);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn copy_width_profile(&mut self, profile: &WidthProfile) {
+        let text = format!("{}\t{}", WIDTH_PROFILE_CLIPBOARD_MIMETYPE, profile.to_json());
+        self.clipboard
+            .with(|c| {
+                c.set_text(text.clone()).unwrap_or_else(|e| {
+                    gui::error!("Clipboard issue—couldn't copy! {}", e);
+                })
+            })
+            .unwrap_or(());
+    }
+
+    pub fn paste_width_profile(&mut self) -> Result<WidthProfile, String> {
+        let text = match self.clipboard.with(|c| c.get_text()) {
+            Some(text) => text.map_err(|e| e.to_string())?,
+            None => return Err("Can't access the clipboard.".to_string()),
+        };
+        match text.trim().split_once('\t') {
+            Some((WIDTH_PROFILE_CLIPBOARD_MIMETYPE, data)) => {
+                let json: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+                WidthProfile::from_json(&json)
+            }
+            _ => Err("The clipboard doesn't hold copied widths.".to_string()),
+        }
+    }
+}
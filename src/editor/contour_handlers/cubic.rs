@@ -1,5 +1,6 @@
 use glifparser::{glif::{contour_operations::ContourOperation, contour::{MFEKContourCommon, MFEKCommonOuter}}, WhichHandle};
 
+use crate::editor::operation_stack::splice_sources;
 use crate::editor::Editor;
 
 use super::ContourHandler;
@@ -9,6 +10,7 @@ pub struct CubicHandler;
 
 impl ContourHandler for CubicHandler {
     fn merge_contours(&self, v: &mut Editor, start_contour: usize, end_contour: usize) {
+        let contour_count = v.get_active_layer_ref().outline.len();
         let (cidx, pidx) = {
             let layer = v.get_active_layer_mut();
 
@@ -29,6 +31,7 @@ impl ContourHandler for CubicHandler {
 
             (selected, p_idx)
         };
+        v.remap_operation_stacks(&splice_sources(contour_count, start_contour, 1, 0));
 
         v.contour_idx = Some(cidx);
         v.point_idx = Some(pidx);
@@ -1,3 +1,251 @@
+// Commands that reshape curves at the selected points without moving the rest of the outline.
+//
+// Harmonizing makes a smooth point curvature continuous (G2) as well as tangent continuous (G1).
+// The curvature a cubic has where it leaves an on-curve point is 2/3 of the distance of its second
+// handle from the tangent, over the square of its first handle's length, so curvature can be
+// matched on both sides either by changing the ratio of the point's handle lengths or, keeping
+// the handles where they are, by sliding the point between them.
+//
+// Balancing evens out the tension of a curve's two handles, each handle's tension being how far
+// it reaches towards where the two handles' lines cross.
+
+use glifparser::{glif::contour::MFEKContourCommon as _, Handle, MFEKPointData, Point};
+use MFEKmath::{vec2, Vector};
+
+use super::Editor;
+use crate::constants::HARMONIZE_MIN_DISTANCE;
+
+use std::collections::BTreeSet;
+
+fn position(point: &Point<MFEKPointData>) -> Vector {
+    vec2![point.x, point.y]
+}
+
+fn handle_position(point: &Point<MFEKPointData>, handle: Handle) -> Vector {
+    match handle {
+        Handle::At(x, y) => vec2![x, y],
+        Handle::Colocated => position(point),
+    }
+}
+
+fn set_handle(handle: &mut Handle, at: Vector) {
+    *handle = Handle::At(at.x as f32, at.y as f32);
+}
+
+/// Signed distance of p from the line through a and b.
+fn side_distance(p: Vector, a: Vector, b: Vector) -> f64 {
+    let line = b - a;
+    let len = line.magnitude();
+    if len < f64::EPSILON {
+        return 0.;
+    }
+    line.cross(p - a) / len
+}
+
+/// A smooth point's neighbours' facing handles: the far control points of the curves on either
+/// side of it, which along with the point's own handles decide its curvature.
+fn far_handles(contour: &[Point<MFEKPointData>], pi: usize, closed: bool) -> Option<(Vector, Vector)> {
+    let len = contour.len();
+    let (prev, next) = if closed {
+        ((pi + len - 1) % len, (pi + 1) % len)
+    } else if pi > 0 && pi + 1 < len {
+        (pi - 1, pi + 1)
+    } else {
+        return None;
+    };
+    let prev = &contour[prev];
+    let next = &contour[next];
+    Some((handle_position(prev, prev.a), handle_position(next, next.b)))
+}
+
+/// The point's handles, if it has two, and they point in opposite directions.
+fn smooth_handles(point: &Point<MFEKPointData>) -> Option<(Vector, Vector)> {
+    let (b, a) = match (point.b, point.a) {
+        (Handle::At(bx, by), Handle::At(ax, ay)) => (vec2![bx, by], vec2![ax, ay]),
+        _ => return None,
+    };
+    let p = position(point);
+    let (incoming, outgoing) = (p - b, a - p);
+    if incoming.magnitude() < f64::EPSILON || outgoing.magnitude() < f64::EPSILON {
+        return None;
+    }
+    if incoming.normalize().dot(outgoing.normalize()) < 0.999 {
+        return None;
+    }
+    Some((b, a))
+}
+
+/// Moves a smooth point's handles, or with move_point the point itself, so the curvature on both
+/// sides of it matches. Returns false if it can't be done, i.e. the point's an inflection or one of
+/// its curves is straight.
+fn harmonize_point(contour: &mut [Point<MFEKPointData>], pi: usize, closed: bool, move_point: bool) -> bool {
+    let (b, a) = match smooth_handles(&contour[pi]) {
+        Some(handles) => handles,
+        None => return false,
+    };
+    let (far_b, far_a) = match far_handles(contour, pi, closed) {
+        Some(far) => far,
+        None => return false,
+    };
+
+    let d_in = side_distance(far_b, b, a);
+    let d_out = side_distance(far_a, b, a);
+    if d_in * d_out <= 0. || d_in.abs() < HARMONIZE_MIN_DISTANCE || d_out.abs() < HARMONIZE_MIN_DISTANCE {
+        return false;
+    }
+    // For equal curvature, the incoming handle's length over the outgoing one's is this.
+    let ratio = (d_in / d_out).sqrt();
+    let t = ratio / (ratio + 1.);
+
+    let point = &mut contour[pi];
+    if move_point {
+        let at = b + (a - b) * t;
+        point.x = at.x as f32;
+        point.y = at.y as f32;
+    } else {
+        let p = position(point);
+        let direction = (a - b).normalize();
+        let span = (a - p).magnitude() + (p - b).magnitude();
+        set_handle(&mut point.b, p - direction * (span * t));
+        set_handle(&mut point.a, p + direction * (span * (1. - t)));
+    }
+    true
+}
+
+/// Gives both handles of the curve from contour[from] to contour[to] the tension of their
+/// average. Returns false for lines, and curves whose handles don't cross ahead of them.
+fn balance_curve(contour: &mut [Point<MFEKPointData>], from: usize, to: usize) -> bool {
+    let (start, end) = (position(&contour[from]), position(&contour[to]));
+    let (h0, h1) = match (contour[from].a, contour[to].b) {
+        (Handle::At(x0, y0), Handle::At(x1, y1)) => (vec2![x0, y0], vec2![x1, y1]),
+        _ => return false,
+    };
+    let crossing = match flo_curves::line::ray_intersects_ray(&(start, h0), &(end, h1)) {
+        Some(crossing) => crossing,
+        None => return false,
+    };
+    // The lines through the handles cross behind one of them, as in an S curve.
+    if (h0 - start).dot(crossing - start) <= 0. || (h1 - end).dot(crossing - end) <= 0. {
+        return false;
+    }
+    let (reach0, reach1) = ((crossing - start).magnitude(), (crossing - end).magnitude());
+    if reach0 < f64::EPSILON || reach1 < f64::EPSILON {
+        return false;
+    }
+    let tension = ((h0 - start).magnitude() / reach0 + (h1 - end).magnitude() / reach1) / 2.;
+    set_handle(&mut contour[from].a, start + (crossing - start) * tension);
+    set_handle(&mut contour[to].b, end + (crossing - end) * tension);
+    true
+}
+
 impl Editor {
-    
-}
\ No newline at end of file
+    fn selected_points(&self) -> BTreeSet<(usize, usize)> {
+        self.selected.iter().copied().chain(self.selected_point()).collect()
+    }
+
+    /// Makes the selected smooth points curvature continuous, by changing the lengths of their
+    /// handles or, with move_points, by sliding them between their handles. Returns how many
+    /// points were changed.
+    pub fn harmonize_selection(&mut self, move_points: bool) -> usize {
+        let selected = self.selected_points();
+        if selected.is_empty() || self.is_modifying() {
+            return 0;
+        }
+
+        let mut outline = self.get_active_layer_ref().outline.clone();
+        let mut changed = 0;
+        for (ci, pi) in selected {
+            let closed = !outline[ci].is_open();
+            if let Some(contour) = outline[ci].cubic_mut() {
+                if harmonize_point(contour, pi, closed, move_points) {
+                    changed += 1;
+                }
+            }
+        }
+        if changed > 0 {
+            self.begin_modification("Harmonize.", false);
+            self.get_active_layer_mut().outline = outline;
+            self.end_modification();
+        }
+        changed
+    }
+
+    /// Evens out the tension of the handles of the curves the selected points are on. Returns how
+    /// many curves were changed.
+    pub fn balance_selection(&mut self) -> usize {
+        let selected = self.selected_points();
+        if selected.is_empty() || self.is_modifying() {
+            return 0;
+        }
+
+        let mut outline = self.get_active_layer_ref().outline.clone();
+        let mut curves = BTreeSet::new();
+        for &(ci, pi) in &selected {
+            let len = outline[ci].len();
+            let closed = !outline[ci].is_open();
+            if pi + 1 < len || closed {
+                curves.insert((ci, pi, (pi + 1) % len));
+            }
+            if pi > 0 || closed {
+                curves.insert((ci, (pi + len - 1) % len, pi));
+            }
+        }
+        let mut changed = 0;
+        for (ci, from, to) in curves {
+            if let Some(contour) = outline[ci].cubic_mut() {
+                if balance_curve(contour, from, to) {
+                    changed += 1;
+                }
+            }
+        }
+        if changed > 0 {
+            self.begin_modification("Balance handles.", false);
+            self.get_active_layer_mut().outline = outline;
+            self.end_modification();
+        }
+        changed
+    }
+}
+
+#[test]
+fn harmonize_point_test() {
+    let point = |x: f32, y: f32, b: Handle, a: Handle| {
+        Point::<MFEKPointData>::from_x_y_a_b_type((x, y), (a, b), glifparser::PointType::Curve)
+    };
+    // A smooth point at the origin on a horizontal tangent, with a flatter curve before it.
+    let mut contour = vec![
+        point(-300., 50., Handle::Colocated, Handle::At(-200., 10.)),
+        point(0., 0., Handle::At(-100., 0.), Handle::At(100., 0.)),
+        point(300., 200., Handle::At(200., 200.), Handle::Colocated),
+    ];
+    assert!(harmonize_point(&mut contour, 1, false, false));
+    let (b, a) = smooth_handles(&contour[1]).unwrap();
+    // The curvature on either side is now the same.
+    let k_in = side_distance(vec2![-200., 10.], b, a) / (b.magnitude() * b.magnitude());
+    let k_out = side_distance(vec2![200., 200.], b, a) / (a.magnitude() * a.magnitude());
+    assert!((k_in - k_out).abs() < 1e-9);
+    // The ends of a contour can't be harmonized.
+    assert!(!harmonize_point(&mut contour, 0, false, false));
+}
+
+#[test]
+fn balance_curve_test() {
+    let point = |x: f32, y: f32, b: Handle, a: Handle| {
+        Point::<MFEKPointData>::from_x_y_a_b_type((x, y), (a, b), glifparser::PointType::Curve)
+    };
+    // A quarter circle-ish curve whose handles cross at (0, 100), with tensions of 0.5 and 0.8.
+    let mut contour = vec![
+        point(0., 0., Handle::Colocated, Handle::At(0., 50.)),
+        point(100., 100., Handle::At(20., 100.), Handle::Colocated),
+    ];
+    assert!(balance_curve(&mut contour, 0, 1));
+    assert_eq!(contour[0].a, Handle::At(0., 65.));
+    assert_eq!(contour[1].b, Handle::At(35., 100.));
+    // Handles whose lines only cross behind one of them, as in an S curve, are left alone.
+    let mut contour = vec![
+        point(0., 0., Handle::Colocated, Handle::At(0., -50.)),
+        point(100., 100., Handle::At(20., 100.), Handle::Colocated),
+    ];
+    assert!(!balance_curve(&mut contour, 0, 1));
+    assert_eq!(contour[0].a, Handle::At(0., -50.));
+}
@@ -27,6 +27,11 @@ impl Editor {
                             },
                         );
                         log::info!("Reloaded UFO-sourced metadata, fontinfo.plist changed");
+                    } else if p.file_name() == Some(oss("kerning.plist"))
+                        || p.file_name() == Some(oss("groups.plist"))
+                        || p.file_name() == Some(oss("contents.plist"))
+                    {
+                        i.kerning_preview.invalidate();
                     } else if p.extension() == Some(oss("glif"))
                         || p.extension() == Some(oss("glifjson"))
                     {
@@ -59,6 +64,11 @@ impl Editor {
                                 ufo_or_dir,
                                 p.file_name().unwrap()
                             );
+                            i.kerning_preview.invalidate_glyph(&p);
+
+                            if self.links_pattern_glyph(&p) && !self.pending_pattern_reloads.contains(&p) {
+                                self.pending_pattern_reloads.push(p);
+                            }
                         }
                     } else {
                         log::debug!("Ignored write of file {:?}", p)
@@ -68,5 +78,16 @@ impl Editor {
                 Err(_) => panic!("Filesystem watcher disconnected!"),
             }
         }
+
+        // Patterns aren't reloaded in the middle of e.g. a drag, as cancelling it would undo them.
+        if !self.is_modifying() && !self.pending_pattern_reloads.is_empty() {
+            let paths = std::mem::take(&mut self.pending_pattern_reloads);
+            for p in paths {
+                match self.refresh_pattern_glyphs(Some(&p)) {
+                    Ok(n) => log::info!("Reloaded {} pattern(s) from {:?}", n, p.file_name().unwrap()),
+                    Err(e) => log::warn!("Failed to reload linked pattern: {}", e),
+                }
+            }
+        }
     }
 }
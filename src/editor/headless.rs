@@ -1,5 +1,10 @@
+use super::operation_stack::OPERATION_STACKS_LIB_KEY;
 use super::Editor;
 use crate::args::HeadlessMode;
+use crate::render;
+use crate::util;
+
+use glifparser::glif::Lib;
 
 use std::cell::RefCell;
 use std::process;
@@ -18,7 +23,9 @@ impl Editor {
             Some(filename) => filename.clone(),
             None => panic!("Cannot go headless without a .glif file to work on"),
         };
-        self.load_glif_impl(filename);
+        if let Err(e) = self.load_glif_impl(filename) {
+            util::hard_error(&e);
+        }
 
         if self.args.no_contour_ops {
             let glyph = self.glyph.as_mut().unwrap();
@@ -27,6 +34,9 @@ impl Editor {
                     contour.set_operation(None);
                 }
             }
+            if let Lib::Plist(lib) = &mut glyph.lib {
+                lib.remove(OPERATION_STACKS_LIB_KEY);
+            }
         }
 
         match self.args.headless_mode {
@@ -40,6 +50,14 @@ impl Editor {
             HeadlessMode::Flatten => {
                 self.flatten_glif(None, false).unwrap();
             }
+            HeadlessMode::Render => {
+                self.rebuild_preview();
+                let options = self.args.render_options.clone().unwrap();
+                if let Err(e) = render::headless::render_to_file(self, &options) {
+                    log::error!("{}", e);
+                    process::exit(1);
+                }
+            }
             HeadlessMode::RunScript => unimplemented!(),
         }
         process::exit(0)
@@ -45,7 +45,10 @@ impl Editor {
                 .redo_stack
                 .push(entry_from_desc_and_editor("Undo", self));
 
+            // Where the glyph's saved isn't part of its history: saving a renamed glyph moves it.
+            let filename = self.glyph.as_ref().and_then(|g| g.filename.clone());
             self.glyph = Some(undo_entry.glyph.clone());
+            self.glyph.as_mut().unwrap().filename = filename;
             self.layer_idx = undo_entry.layer_idx;
             self.contour_idx = undo_entry.contour_idx;
             self.point_idx = undo_entry.point_idx;
@@ -77,7 +80,10 @@ impl Editor {
                 glyph: self.glyph.as_ref().unwrap().clone(),
             });
 
+            // As in undo, the glyph stays where it's saved.
+            let filename = self.glyph.as_ref().and_then(|g| g.filename.clone());
             self.glyph = Some(redo_entry.glyph.clone());
+            self.glyph.as_mut().unwrap().filename = filename;
             self.layer_idx = redo_entry.layer_idx;
             self.contour_idx = redo_entry.contour_idx;
             self.point_idx = redo_entry.point_idx;
@@ -0,0 +1,144 @@
+// UFO images must be PNGs, so images in other formats are converted when they're placed. The PNG
+// goes in the UFO's images directory, and the original is kept in the UFO's data directory, where
+// any kind of file is allowed, so it isn't lost if someone wants to redo the conversion. Outside of
+// a UFO both files are kept next to each other.
+
+use image::{self, ImageOutputFormat};
+use skia_safe::{self as skia, EncodedImageFormat, FontMgr};
+use xmltree;
+
+use super::Editor;
+use crate::constants::SVG_IMAGE_RASTER_SIZE;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extensions of the image formats that can be placed, PNG included.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "tif", "tiff", "svg"];
+/// Where, in a UFO's data directory, originals of converted images are kept.
+pub const ORIGINAL_IMAGES_DATA_DIR: &str = "org.mfek.originalImages";
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+pub fn needs_conversion(path: &Path) -> bool {
+    extension(path) != "png"
+}
+
+/// Reads an SVG's size from its root element, falling back to its viewBox.
+fn svg_size(data: &[u8]) -> Result<(f32, f32), String> {
+    let root = xmltree::Element::parse(data).map_err(|e| format!("Invalid SVG: {}", e))?;
+    let length = |name: &str| {
+        root.attributes
+            .get(name)
+            .and_then(|l| l.trim_end_matches("px").trim().parse::<f32>().ok())
+    };
+    let view_box: Vec<f32> = root
+        .attributes
+        .get("viewBox")
+        .map(|vb| {
+            vb.split(|c: char| c == ',' || c.is_whitespace())
+                .filter_map(|n| n.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    let size = match (length("width"), length("height")) {
+        (Some(w), Some(h)) => (w, h),
+        _ if view_box.len() == 4 => (view_box[2], view_box[3]),
+        _ => return Err("SVG has neither a size nor a viewBox.".to_string()),
+    };
+    if size.0 <= 0. || size.1 <= 0. {
+        return Err("SVG has no area.".to_string());
+    }
+    Ok(size)
+}
+
+/// Rasterizes an SVG so its longest side is SVG_IMAGE_RASTER_SIZE pixels.
+fn svg_to_png(data: &[u8]) -> Result<Vec<u8>, String> {
+    let (width, height) = svg_size(data)?;
+    let scale = SVG_IMAGE_RASTER_SIZE / width.max(height);
+    let mut dom = skia::svg::Dom::from_bytes(data, FontMgr::new())
+        .map_err(|e| format!("Couldn't read SVG: {:?}", e))?;
+    dom.set_container_size((width, height));
+
+    let size = ((width * scale).ceil() as i32, (height * scale).ceil() as i32);
+    let mut surface = skia::surfaces::raster_n32_premul(size)
+        .ok_or_else(|| "Failed to create raster surface".to_string())?;
+    let canvas = surface.canvas();
+    canvas.clear(skia::Color::TRANSPARENT);
+    canvas.scale((scale, scale));
+    dom.render(canvas);
+
+    surface
+        .image_snapshot()
+        .encode(None, EncodedImageFormat::PNG, None)
+        .map(|png| png.as_bytes().to_vec())
+        .ok_or_else(|| "Failed to encode PNG".to_string())
+}
+
+fn raster_to_png(path: &Path) -> Result<Vec<u8>, String> {
+    let img = image::open(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let mut png = vec![];
+    img.write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|e| format!("Couldn't convert {} to PNG: {}", path.display(), e))?;
+    Ok(png)
+}
+
+/// The first of dir/stem.ext, dir/stem-1.ext… that doesn't exist yet.
+fn unused_path(dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    let mut ret = dir.join(format!("{}.{}", stem, ext));
+    let mut n = 1;
+    while ret.exists() {
+        ret = dir.join(format!("{}-{}.{}", stem, n, ext));
+        n += 1;
+    }
+    ret
+}
+
+impl Editor {
+    /// Converts the image at path to a PNG, stores it and the original (see above), and returns
+    /// the PNG's path.
+    pub fn convert_image_to_png(&self, path: &Path) -> Result<PathBuf, String> {
+        let ext = extension(path);
+        if !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            return Err(format!("{} isn't an image format MFEKglif can place.", path.display()));
+        }
+
+        let png = if ext == "svg" {
+            let data = fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+            svg_to_png(&data)?
+        } else {
+            raster_to_png(path)?
+        };
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "image".to_string());
+        let (images_dir, originals_dir) = match self.font_dir() {
+            Some(font) => (font.join("images"), font.join("data").join(ORIGINAL_IMAGES_DATA_DIR)),
+            None => {
+                let dir = path.parent().map(|p| p.to_owned()).unwrap_or_default();
+                (dir.clone(), dir)
+            }
+        };
+        let write_err = |p: &Path, e: std::io::Error| format!("Couldn't write {}: {}", p.display(), e);
+
+        fs::create_dir_all(&images_dir).map_err(|e| write_err(&images_dir, e))?;
+        let png_path = unused_path(&images_dir, &stem, "png");
+        fs::write(&png_path, png).map_err(|e| write_err(&png_path, e))?;
+
+        if originals_dir != path.parent().unwrap_or(Path::new("")) {
+            fs::create_dir_all(&originals_dir).map_err(|e| write_err(&originals_dir, e))?;
+            // Named after the PNG, so the two can be matched up.
+            let png_stem = png_path.file_stem().unwrap().to_string_lossy().into_owned();
+            let original = unused_path(&originals_dir, &png_stem, &ext);
+            fs::copy(path, &original).map_err(|e| write_err(&original, e))?;
+        }
+
+        Ok(png_path)
+    }
+}
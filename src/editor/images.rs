@@ -1,21 +1,114 @@
 use glifparser::{
+    glif::{Lib, MFEKGlif},
     image::{DataLoadState, DataOrBitmap},
-    Color as GlifColor, GlifImage, Image as GpImage,
+    Color as GlifColor, GlifImage, Image as GpImage, MFEKPointData,
 };
 use skia_safe::{
     AlphaType as SkAlphaType, ColorInfo as SkColorInfo, ColorSpace as SkColorSpace,
     ColorType as SkColorType, ImageInfo as SkImageInfo,
 };
-use skia_safe::{Data as SkData, Image as SkImage, Matrix};
+use skia_safe::{Data as SkData, Image as SkImage, Matrix, Rect as SkRect};
 
 use glifparser::matrix::ToSkiaMatrix;
 
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::image_import;
+use super::operation_stack::splice_sources;
 use super::Editor;
 
+/// Where per-image display settings are kept in the glyph's lib, as a dictionary keyed by layer
+/// and image index, like "0/1" for the second image of the first layer. An image placed more than
+/// once has settings for each placement.
+pub const IMAGE_SETTINGS_LIB_KEY: &str = "org.mfek.imageSettings";
+
+fn key(layer: usize, image: usize) -> String {
+    format!("{}/{}", layer, image)
+}
+
+fn store_image_settings(glif: &mut MFEKGlif<MFEKPointData>, images: &plist::Dictionary) {
+    if !matches!(glif.lib, Lib::Plist(_)) {
+        glif.lib = Lib::Plist(plist::Dictionary::new());
+    }
+    if let Lib::Plist(lib) = &mut glif.lib {
+        if images.is_empty() {
+            lib.remove(IMAGE_SETTINGS_LIB_KEY);
+        } else {
+            lib.insert(IMAGE_SETTINGS_LIB_KEY.to_string(), plist::Value::Dictionary(images.clone()));
+        }
+    }
+}
+
+/// How a background image is shown. The tint is the image's own color, which is part of the
+/// .glif format; these are kept in the lib.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImageSettings {
+    pub opacity: f32,
+    pub visible: bool,
+    /// Locked images can't be selected with the image tool.
+    pub locked: bool,
+    /// How much to cut off the left, top, right and bottom of the image, in its pixels.
+    pub crop: [f32; 4],
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            opacity: 1.,
+            visible: true,
+            locked: false,
+            crop: [0.; 4],
+        }
+    }
+}
+
+impl ImageSettings {
+    fn from_plist(dict: &plist::Dictionary) -> Self {
+        let mut ret = Self::default();
+        if let Some(opacity) = dict.get("opacity").and_then(|v| v.as_real()) {
+            ret.opacity = opacity as f32;
+        }
+        if let Some(visible) = dict.get("visible").and_then(|v| v.as_boolean()) {
+            ret.visible = visible;
+        }
+        if let Some(locked) = dict.get("locked").and_then(|v| v.as_boolean()) {
+            ret.locked = locked;
+        }
+        if let Some(crop) = dict.get("crop").and_then(|v| v.as_array()) {
+            for (c, v) in ret.crop.iter_mut().zip(crop) {
+                *c = v.as_real().unwrap_or(0.) as f32;
+            }
+        }
+        ret
+    }
+
+    fn to_plist(&self) -> plist::Value {
+        let mut dict = plist::Dictionary::new();
+        dict.insert("opacity".to_string(), plist::Value::Real(self.opacity as f64));
+        dict.insert("visible".to_string(), plist::Value::Boolean(self.visible));
+        dict.insert("locked".to_string(), plist::Value::Boolean(self.locked));
+        dict.insert(
+            "crop".to_string(),
+            plist::Value::Array(self.crop.iter().map(|c| plist::Value::Real(*c as f64)).collect()),
+        );
+        plist::Value::Dictionary(dict)
+    }
+
+    /// The part of an image of the given size left after cropping, in its pixels.
+    pub fn crop_rect(&self, (width, height): (i32, i32)) -> SkRect {
+        let [left, top, right, bottom] = self.crop;
+        let (width, height) = (width as f32, height as f32);
+        SkRect::new(
+            left.clamp(0., width),
+            top.clamp(0., height),
+            (width - right).clamp(left.clamp(0., width), width),
+            (height - bottom).clamp(top.clamp(0., height), height),
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct EditorImage {
     pub img: SkImage,
@@ -95,22 +188,168 @@ impl Editor {
         });
     }
 
-    /// This function must be called between begin_layer_modification and end_layer_modification
-    pub fn add_image_to_active_layer(&mut self, path: PathBuf) {
-        // okay so first we've got to take our pathbuf and we've got to convert it into a glyph image
-        let image_result = GlifImage::from_filename(path);
-
-        let image = match image_result {
-            Ok(image) => image,
-            Err(_) => todo!(), // need to add some way to tell the user we failed to load the image
-                               // a pop up box or something should do but I'm not super concerned about this at the moment
+    /// This function must be called between begin_layer_modification and end_layer_modification.
+    /// Images that aren't PNGs are converted first. If the image can't be read the layer is left
+    /// as it was.
+    pub fn add_image_to_active_layer(&mut self, path: PathBuf) -> Result<(), String> {
+        let path = if image_import::needs_conversion(&path) {
+            self.convert_image_to_png(&path)?
+        } else {
+            path
         };
 
+        // okay so first we've got to take our pathbuf and we've got to convert it into a glyph image
+        let image = GlifImage::from_filename(path.clone())
+            .map_err(|e| format!("Couldn't open {}: {:?}", path.display(), e))?;
+        let filename = image.filename.clone();
+
         let matrix = image.matrix();
         self.get_active_layer_mut()
             .images
             .push((image, matrix.into()));
 
         self.recache_images();
+        if !self.images.contains_key(&filename) {
+            self.get_active_layer_mut().images.pop();
+            return Err(format!(
+                "{} isn't an image MFEKglif can read.",
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Images the glyph refers to that couldn't be read, e.g. because they were moved or deleted.
+    /// They stay in the glyph, and are saved with it, until they're relinked or removed.
+    pub fn missing_images(&self) -> Vec<PathBuf> {
+        self.with_glyph(|glif| {
+            let mut ret = vec![];
+            for layer in &glif.layers {
+                for (image, _matrix) in &layer.images {
+                    if !self.images.contains_key(&image.filename) && !ret.contains(&image.filename) {
+                        ret.push(image.filename.clone());
+                    }
+                }
+            }
+            ret
+        })
+    }
+
+    /// Points every use of the image filename, on any layer, at the image at path instead, keeping
+    /// their placements and settings. This function must be called between begin_modification and
+    /// end_modification.
+    pub fn relink_image(&mut self, filename: &Path, path: PathBuf) -> Result<(), String> {
+        let path = if image_import::needs_conversion(&path) {
+            self.convert_image_to_png(&path)?
+        } else {
+            path
+        };
+        let new_filename = GlifImage::from_filename(path.clone())
+            .map_err(|e| format!("Couldn't open {}: {:?}", path.display(), e))?
+            .filename;
+
+        self.with_glyph_mut(|glif| {
+            for layer in glif.layers.iter_mut() {
+                for (image, _matrix) in layer.images.iter_mut() {
+                    if image.filename.as_path() == filename {
+                        image.filename = new_filename.clone();
+                    }
+                }
+            }
+        });
+        self.recache_images();
+
+        if self.images.contains_key(&new_filename) {
+            Ok(())
+        } else {
+            Err(format!("{} isn't an image MFEKglif can read.", path.display()))
+        }
+    }
+
+    /// Where an image's file is. In a UFO, file names are relative to its images directory;
+    /// outside of one, to the glyph's directory.
+    pub fn image_path(&self, filename: &Path) -> PathBuf {
+        if filename.is_absolute() {
+            return filename.to_owned();
+        }
+        match self.font_dir() {
+            Some(font) => font.join("images").join(filename),
+            None => self
+                .with_glyph(|glif| glif.filename.as_ref()?.parent().map(|p| p.join(filename)))
+                .unwrap_or_else(|| filename.to_owned()),
+        }
+    }
+
+    fn image_settings_dict(&self) -> plist::Dictionary {
+        self.with_glyph(|glif| match &glif.lib {
+            Lib::Plist(lib) => lib
+                .get(IMAGE_SETTINGS_LIB_KEY)
+                .and_then(|v| v.as_dictionary())
+                .cloned()
+                .unwrap_or_default(),
+            _ => plist::Dictionary::new(),
+        })
+    }
+
+    /// How the image at index ii of layer li is shown.
+    pub fn image_settings(&self, li: usize, ii: usize) -> ImageSettings {
+        self.with_glyph(|glif| match &glif.lib {
+            Lib::Plist(lib) => lib
+                .get(IMAGE_SETTINGS_LIB_KEY)
+                .and_then(|v| v.as_dictionary())
+                .and_then(|images| images.get(&key(li, ii)))
+                .and_then(|v| v.as_dictionary())
+                .map(ImageSettings::from_plist)
+                .unwrap_or_default(),
+            _ => ImageSettings::default(),
+        })
+    }
+
+    /// This function must be called between begin_modification and end_modification.
+    pub fn set_image_settings(&mut self, li: usize, ii: usize, settings: ImageSettings) {
+        let mut images = self.image_settings_dict();
+        if settings == ImageSettings::default() {
+            images.remove(&key(li, ii));
+        } else {
+            images.insert(key(li, ii), settings.to_plist());
+        }
+        self.with_glyph_mut(|glif| store_image_settings(glif, &images));
+    }
+
+    /// Removes an image from the active layer, and its settings along with it. This function must
+    /// be called between begin_modification and end_modification.
+    pub fn remove_image(&mut self, ii: usize) {
+        let li = self.layer_idx.unwrap();
+        let count = self.get_active_layer_ref().images.len();
+        self.get_active_layer_mut().images.remove(ii);
+        let sources = splice_sources(count, ii, 1, 0);
+
+        let mut images = self.image_settings_dict();
+        let old: Vec<Option<plist::Value>> = (0..count).map(|ii| images.remove(&key(li, ii))).collect();
+        for (new, source) in sources.iter().enumerate() {
+            if let Some(settings) = source.and_then(|s| old[s].clone()) {
+                images.insert(key(li, new), settings);
+            }
+        }
+        self.with_glyph_mut(|glif| store_image_settings(glif, &images));
+        self.recache_images();
+    }
+
+    /// Carries every layer's image settings along when layers are removed or reordered, as
+    /// remap_layer_operation_stacks does their stacks.
+    pub fn remap_layer_image_settings(&mut self, sources: &[Option<usize>]) {
+        let old = self.image_settings_dict();
+        let mut images = plist::Dictionary::new();
+        for (new, source) in sources.iter().enumerate() {
+            if let Some(source) = source {
+                let prefix = format!("{}/", source);
+                for (k, settings) in old.iter() {
+                    if let Some(ii) = k.strip_prefix(&prefix) {
+                        images.insert(format!("{}/{}", new, ii), settings.clone());
+                    }
+                }
+            }
+        }
+        self.with_glyph_mut_no_history(|glif| store_image_settings(glif, &images));
     }
 }
@@ -1,12 +1,14 @@
-use super::{events::*, Editor};
+use super::{events::*, history::History, Editor};
 
 use glifparser::glif::contour::MFEKContourCommon;
 use MFEKmath::mfek::ResolveCubic;
 //use fs2::FileExt as _; # TODO: Add file locking.
 use glifparser::glif::mfek::{Layer, MFEKGlif};
+use glifparser::glif::Lib;
 use glifparser::{Glif, MFEKPointData};
 use log;
 use mfek_ipc::IPCInfo;
+use kurbo::Affine;
 use plist;
 
 use std::{
@@ -16,9 +18,12 @@ use std::{
     rc::Rc,
 };
 
+use super::image_import::IMAGE_EXTENSIONS;
+use super::images::IMAGE_SETTINGS_LIB_KEY;
+use super::operation_stack::OPERATION_STACKS_LIB_KEY;
 use crate::filedialog;
 use crate::user_interface::{InputPrompt, Interface};
-use crate::util::DEBUG_DUMP_GLYPH;
+use crate::util::{self, DEBUG_DUMP_GLYPH};
 
 impl Editor {
     pub fn just_saved(&self) -> bool {
@@ -41,16 +46,38 @@ impl Editor {
         self.with_glyph(|g| g.filename.as_ref().unwrap().clone())
     }
 
+    /// Loads a glyph, replacing the one being edited. If it can't be read, the current glyph is
+    /// kept and the user told why, unless there's none yet, as at startup, which is fatal.
     pub fn load_glif<F: AsRef<Path> + Clone>(&mut self, interface: &mut Interface, filename: F) {
+        if let Err(message) = self.load_glif_impl(filename.clone()) {
+            if self.glyph.is_none() {
+                util::hard_error(&message);
+            }
+            interface.push_prompt(InputPrompt::Message { message });
+            return;
+        }
         interface
             .set_window_title(&format!(
                 "MFEKglif — {}",
                 filename.as_ref().to_str().unwrap()
             ))
             .expect("Failed to set SDL2 window title");
-        self.load_glif_impl(filename.clone());
         self.mark_preview_dirty();
         self.rebuild(interface);
+        let missing = self.missing_images();
+        if !missing.is_empty() {
+            let missing = missing
+                .iter()
+                .map(|f| f.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n");
+            interface.push_prompt(InputPrompt::Message {
+                message: format!(
+                    "These images couldn't be read, and can be relinked with the image tool:\n{}",
+                    missing
+                ),
+            });
+        }
         self.dispatch_editor_event(
             interface,
             EditorEvent::IOEvent {
@@ -60,29 +87,96 @@ impl Editor {
         );
     }
 
-    pub fn load_glif_impl<F: AsRef<Path> + Clone>(&mut self, file: F) {
-        // TODO: Actually handle errors now that we have them.
+    /// Like load_glif, but asks first if there are unsaved changes.
+    pub fn open_glif(&mut self, i: &mut Interface, filename: PathBuf) {
+        if !self.has_unsaved_changes() {
+            self.load_glif(i, &filename);
+            return;
+        }
+        i.push_prompt(InputPrompt::YesNo {
+            question: format!(
+                "Unsaved changes exist in glyph. Open {} anyway?",
+                filename.display()
+            ),
+            afterword: String::new(),
+            func: Rc::new(move |v: &mut Editor, i: &mut Interface, open: bool| {
+                if open {
+                    log::warn!("Opening {:?}, discarding unsaved changes", filename);
+                    v.load_glif(i, &filename);
+                }
+            }),
+        });
+    }
+
+    /// Handles a file dropped onto the window: glyphs are opened, SVGs imported as outlines and
+    /// images placed on the active layer, both where the mouse is.
+    pub fn drop_file(&mut self, i: &mut Interface, filename: PathBuf) {
+        let ext = filename
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let position = i.mouse_info.position;
+        let result = match ext.as_str() {
+            "glif" | "glifjson" => {
+                self.open_glif(i, filename);
+                return;
+            }
+            "svg" => {
+                self.begin_modification("Imported SVG.", false);
+                self.import_svg(&filename, Some(position)).map(|_| ())
+            }
+            ext if IMAGE_EXTENSIONS.contains(&ext) => {
+                self.begin_modification("Add image to layer.", false);
+                self.add_image_to_active_layer(filename).map(|()| {
+                    // Images are drawn up and to the right of their origin.
+                    let layer = self.get_active_layer_mut();
+                    if let Some((_, affine)) = layer.images.last_mut() {
+                        *affine = Affine::translate((position.0 as f64, position.1 as f64)) * *affine;
+                    }
+                })
+            }
+            _ => {
+                i.push_prompt(InputPrompt::Message {
+                    message: format!("MFEKglif can't open {}.", filename.display()),
+                });
+                return;
+            }
+        };
+
+        match result {
+            Ok(()) => self.end_modification(),
+            Err(e) => {
+                self.cancel_modification();
+                i.push_prompt(InputPrompt::Message { message: e });
+            }
+        }
+    }
+
+    /// Reads a glyph and makes it the one being edited. The file's read in full first, so if it
+    /// can't be, nothing changes.
+    pub fn load_glif_impl<F: AsRef<Path> + Clone>(&mut self, file: F) -> Result<(), String> {
         let glif: MFEKGlif<_> = {
             let ext = file.as_ref().extension().map(|e| e.to_ascii_lowercase());
             let ext_or = ext
                 .unwrap_or(Oss::from("glif"))
                 .to_string_lossy()
                 .into_owned();
+            let display = file.as_ref().display();
             let mut tempglif: MFEKGlif<_> = match ext_or.as_str() {
                 "glifjson" => {
-                    serde_json::from_str(&fs::read_to_string(&file).expect("Could not open file"))
-                        .expect("Could not deserialize JSON MFEKGlif")
+                    let json = fs::read_to_string(&file)
+                        .map_err(|e| format!("Could not open {}: {}", display, e))?;
+                    serde_json::from_str(&json)
+                        .map_err(|e| format!("{} is not a valid .glifjson: {}", display, e))?
                 }
                 "glif" => glifparser::read_from_filename(&file)
-                    .expect("Invalid glif!")
+                    .map_err(|e| format!("{} is not a valid .glif: {:?}", display, e))?
                     .into(),
                 _ => {
-                    log::error!(
-                        "Refusing to write to a file with extension {}: {:?}",
-                        ext_or,
-                        file.as_ref()
-                    );
-                    return;
+                    return Err(format!(
+                        "Refusing to open a file with extension {}: {}",
+                        ext_or, display
+                    ));
                 }
             };
 
@@ -95,11 +189,25 @@ impl Editor {
             log::debug!("{:#?}", &glif);
         }
 
+        // Reloading the same file keeps its history; another glyph's history and indices don't apply.
+        let switched = self.glyph.as_ref().map(|g| &g.filename) != Some(&glif.filename);
         self.set_glyph(glif);
+        if switched {
+            self.history = History::default();
+            self.contour_idx = None;
+            self.point_idx = None;
+            self.selected.clear();
+            self.operation_stack_preview = None;
+            self.pending_pattern_reloads.clear();
+        }
         self.initialize();
+        Ok(())
     }
 
     pub fn save_glif(&mut self, rename: bool) -> Result<PathBuf, ()> {
+        if !rename {
+            self.sync_glyph_files().map_err(|e| log::error!("{}", e))?;
+        }
         self.begin_modification("Saved glyph", true);
         let res = self.with_glyph_mut(|glyph| {
             let filename: PathBuf = if rename {
@@ -137,6 +245,9 @@ impl Editor {
             self.rebuild(i);
         }
 
+        if !rename {
+            self.sync_glyph_files().map_err(|e| log::error!("{}", e))?;
+        }
         let mut export = self.prepare_export();
         if export.layers.len() > 1 {
             log::warn!("In a flatten operation, layers not in the topmost group will be discarded and not in your chosen file. You may want to export (Ctrl+E) and not flatten.");
@@ -178,6 +289,7 @@ impl Editor {
         if let Some(i) = interface {
             self.rebuild(i);
         }
+        self.sync_glyph_files().map_err(|e| log::error!("{}", e))?;
         let glif_fn = {
             let mut temp = self.filename_or_panic();
             temp.set_extension("glif");
@@ -363,6 +475,10 @@ impl Editor {
     }
 }
 
+/// Lib keys only the editor understands. Exported outlines already have their stacked operations
+/// applied, so keeping the stacks would apply them again when the glif's opened.
+const EDITOR_ONLY_LIB_KEYS: &[&str] = &[OPERATION_STACKS_LIB_KEY, IMAGE_SETTINGS_LIB_KEY];
+
 pub trait ExportLayer {
     fn to_exported(&self, layer: &mut Layer<MFEKPointData>) -> Glif<MFEKPointData>;
 }
@@ -393,6 +509,12 @@ impl ExportLayer for MFEKGlif<MFEKPointData> {
         ret.width = self.width;
         ret.name = self.name.clone();
         ret.unicode = self.unicode.clone();
+        ret.lib = self.lib.clone();
+        if let Lib::Plist(lib) = &mut ret.lib {
+            for key in EDITOR_ONLY_LIB_KEYS {
+                lib.remove(*key);
+            }
+        }
         ret.filename = self.filename.clone();
         ret
     }
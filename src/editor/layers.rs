@@ -1,3 +1,4 @@
+use super::operation_stack::splice_sources;
 use super::Editor;
 use glifparser::glif::{HistoryEntry, Layer, MFEKOutline};
 
@@ -60,7 +61,11 @@ impl Editor {
         });
 
         let lidx = self.layer_idx.unwrap();
+        let layer_count = self.get_layer_count();
         self.with_glyph_mut_no_history(|glyph| glyph.layers.remove(lidx));
+        let sources = splice_sources(layer_count, lidx, 1, 0);
+        self.remap_layer_operation_stacks(&sources);
+        self.remap_layer_image_settings(&sources);
 
         if self.layer_idx != Some(0) {
             self.layer_idx = Some(self.layer_idx.unwrap() - 1);
@@ -122,6 +127,12 @@ impl Editor {
             self.glyph.as_mut().unwrap().layers[dest].operation = None;
         }
 
+        let sources: Vec<Option<usize>> = (0..self.get_layer_count())
+            .map(|li| Some(if li == src { dest } else if li == dest { src } else { li }))
+            .collect();
+        self.remap_layer_operation_stacks(&sources);
+        self.remap_layer_image_settings(&sources);
+
         self.mark_preview_dirty();
     }
 
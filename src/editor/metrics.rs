@@ -0,0 +1,166 @@
+// Horizontal metrics: the advance width and the left and right sidebearings. Changing the left
+// sidebearing means moving the whole glyph, which we do with util::move_all_layers so outlines,
+// components, anchors and images all stay together.
+
+use glifparser::outline::skia::ToSkiaPaths as _;
+use skia_safe::{Canvas, Paint, PaintStyle, Rect};
+
+use super::{util, Editor};
+use crate::constants::SIDEBEARING_HANDLE_SIZE;
+use crate::settings;
+use crate::user_interface::{MouseInfo, RenderState};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sidebearing {
+    Left,
+    Right,
+}
+
+impl Editor {
+    pub fn advance_width(&self) -> u64 {
+        self.with_glyph(|glyph| glyph.width.unwrap_or(0))
+    }
+
+    /// The height of a vertical metric, like "ascender", from the guidelines MFEKmetadata gave us.
+    pub fn vertical_metric(&self, name: &str) -> Option<f32> {
+        self.guidelines
+            .iter()
+            .find(|g| g.name.as_deref() == Some(name))
+            .map(|g| g.at.y)
+    }
+
+    /// The horizontal extent of the visible outlines, with contour operations applied. Returns None
+    /// if the glyph is empty.
+    pub fn outline_bounds(&self) -> Option<Rect> {
+        if self.glyph.is_none() {
+            return None;
+        }
+        let glyph = self.built_glyph();
+
+        let mut bounds: Option<Rect> = None;
+        for layer in glyph.layers.iter().filter(|l| l.visible) {
+            let path = layer.outline.to_skia_paths(None).combined();
+            if path.count_points() == 0 {
+                continue;
+            }
+            let path_bounds = path.compute_tight_bounds();
+            bounds = Some(match bounds {
+                Some(b) => Rect::join2(b, path_bounds),
+                None => path_bounds,
+            });
+        }
+
+        bounds
+    }
+
+    /// Returns the left and right sidebearings, or None if the glyph has no outlines.
+    pub fn sidebearings(&self) -> Option<(f32, f32)> {
+        let bounds = self.outline_bounds()?;
+        Some((bounds.left, self.advance_width() as f32 - bounds.right))
+    }
+
+    /// Sets the advance width without moving anything.
+    pub fn set_advance_width(&mut self, width: u64) {
+        self.begin_modification("Set advance width.", false);
+        self.with_glyph_mut(|glyph| glyph.width = Some(width));
+        self.add_width_guidelines();
+        self.end_modification();
+    }
+
+    pub fn advance_height(&self) -> u64 {
+        self.with_glyph(|glyph| glyph.height.unwrap_or(0))
+    }
+
+    /// Sets the advance height, used when the glyph's set vertically.
+    pub fn set_advance_height(&mut self, height: u64) {
+        self.begin_modification("Set advance height.", false);
+        self.with_glyph_mut(|glyph| glyph.height = Some(height));
+        self.end_modification();
+    }
+
+    /// Sets the left sidebearing by moving the glyph, keeping the right sidebearing as it was.
+    pub fn set_left_sidebearing(&mut self, lsb: f32) {
+        let (old_lsb, _) = match self.sidebearings() {
+            Some(sb) => sb,
+            None => return,
+        };
+        let delta = (lsb - old_lsb).round();
+
+        self.begin_modification("Set left sidebearing.", false);
+        util::move_all_layers(self, -delta, f32::NAN);
+        self.with_glyph_mut(|glyph| {
+            glyph.width = Some((glyph.width.unwrap_or(0) as f32 + delta).max(0.) as u64);
+        });
+        self.add_width_guidelines();
+        self.end_modification();
+    }
+
+    /// Sets the right sidebearing by changing the advance width.
+    pub fn set_right_sidebearing(&mut self, rsb: f32) {
+        let bounds = match self.outline_bounds() {
+            Some(b) => b,
+            None => return,
+        };
+
+        self.set_advance_width((bounds.right + rsb).round().max(0.) as u64);
+    }
+
+    /// Moves the glyph so that its sidebearings are equal, keeping the advance width.
+    pub fn center_glyph(&mut self) {
+        let (lsb, rsb) = match self.sidebearings() {
+            Some(sb) => sb,
+            None => return,
+        };
+        let delta = ((lsb + rsb) / 2. - lsb).round();
+        if delta == 0. {
+            return;
+        }
+
+        self.begin_modification("Center glyph.", false);
+        util::move_all_layers(self, -delta, f32::NAN);
+        self.end_modification();
+    }
+}
+
+/// Returns which sidebearing handle, if any, is under the mouse. The handles sit where the
+/// sidebearing lines cross the baseline.
+pub fn clicked_sidebearing_handle(
+    v: &Editor,
+    i: &RenderState,
+    mouse_info: MouseInfo,
+) -> Option<Sidebearing> {
+    let radius = SIDEBEARING_HANDLE_SIZE / i.viewport.factor;
+    let (mx, my) = mouse_info.position;
+    if my.abs() > radius {
+        return None;
+    }
+
+    if mx.abs() <= radius {
+        Some(Sidebearing::Left)
+    } else if (mx - v.advance_width() as f32).abs() <= radius {
+        Some(Sidebearing::Right)
+    } else {
+        None
+    }
+}
+
+pub fn draw_sidebearing_handles(v: &Editor, i: &RenderState, canvas: &Canvas) {
+    let size = SIDEBEARING_HANDLE_SIZE / i.viewport.factor;
+
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(settings::current().colors.sidebearing_handle);
+    paint.set_style(PaintStyle::Fill);
+
+    for x in [0., v.advance_width() as f32] {
+        // The handles are diamonds, to tell them apart from points.
+        canvas.save();
+        canvas.translate((x, 0.));
+        canvas.rotate(45., None);
+        canvas.draw_rect(
+            Rect::from_xywh(-size / 2., -size / 2., size, size),
+            &paint,
+        );
+        canvas.restore();
+    }
+}
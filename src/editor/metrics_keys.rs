@@ -0,0 +1,326 @@
+// Metrics keys link a glyph's sidebearings or advance width to another glyph in the same UFO, as
+// in Glyphs.app. They're stored in the glyph's lib and look like:
+//
+//     =40      a fixed value
+//     =n       the same side of n
+//     =H+10    the same side of H, plus 10
+//     =|o      the opposite side of o (only makes sense for sidebearings)
+//
+// They're only resolved when asked to, with update_metrics_from_keys, never automatically.
+
+use glifparser::glif::Lib;
+use glifparser::outline::skia::ToSkiaPaths as _;
+use plist;
+
+use super::{util, Editor};
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricsKeySide {
+    Left,
+    Right,
+    Width,
+}
+
+impl MetricsKeySide {
+    pub fn lib_key(&self) -> &'static str {
+        match self {
+            MetricsKeySide::Left => "org.mfek.leftMetricsKey",
+            MetricsKeySide::Right => "org.mfek.rightMetricsKey",
+            MetricsKeySide::Width => "org.mfek.widthMetricsKey",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetricsKey {
+    Value(f32),
+    Glyph {
+        name: String,
+        opposite: bool,
+        offset: f32,
+    },
+}
+
+impl FromStr for MetricsKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .trim()
+            .strip_prefix('=')
+            .ok_or_else(|| format!("Metrics key {:?} must start with =", s))?
+            .trim();
+
+        if let Ok(value) = s.parse::<f32>() {
+            return Ok(MetricsKey::Value(value));
+        }
+
+        let (opposite, s) = match s.strip_prefix('|') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        // Glyph names can't start with a sign, but can hold one, as in a-cy, so the offset is the
+        // first + or - after the name that's followed by nothing but a number.
+        let offset_at = |idx: usize| {
+            s[idx..]
+                .replace(' ', "")
+                .parse::<f32>()
+                .ok()
+                .filter(|offset| offset.is_finite())
+        };
+        let (name, offset) = match s
+            .char_indices()
+            .skip(1)
+            .filter(|(_, c)| *c == '+' || *c == '-')
+            .find_map(|(idx, _)| Some((idx, offset_at(idx)?)))
+        {
+            Some((idx, offset)) => (s[..idx].trim(), offset),
+            None => (s.trim(), 0.),
+        };
+
+        if name.is_empty() {
+            return Err("Metrics key is missing a glyph name".to_string());
+        }
+
+        Ok(MetricsKey::Glyph {
+            name: name.to_string(),
+            opposite,
+            offset,
+        })
+    }
+}
+
+impl fmt::Display for MetricsKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetricsKey::Value(value) => write!(f, "={}", value),
+            MetricsKey::Glyph {
+                name,
+                opposite,
+                offset,
+            } => {
+                write!(f, "={}{}", if *opposite { "|" } else { "" }, name)?;
+                if *offset > 0. {
+                    write!(f, "+{}", offset)?;
+                } else if *offset < 0. {
+                    write!(f, "{}", offset)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[test]
+fn metrics_key_test() {
+    assert_eq!("=40".parse(), Ok(MetricsKey::Value(40.)));
+    assert_eq!(
+        "=H+10".parse(),
+        Ok(MetricsKey::Glyph {
+            name: "H".to_string(),
+            opposite: false,
+            offset: 10.
+        })
+    );
+    assert_eq!(
+        "=|o".parse(),
+        Ok(MetricsKey::Glyph {
+            name: "o".to_string(),
+            opposite: true,
+            offset: 0.
+        })
+    );
+    assert_eq!(
+        "=a.sc-5".parse::<MetricsKey>().map(|k| k.to_string()),
+        Ok("=a.sc-5".to_string())
+    );
+    assert_eq!(
+        "=a-cy".parse(),
+        Ok(MetricsKey::Glyph {
+            name: "a-cy".to_string(),
+            opposite: false,
+            offset: 0.
+        })
+    );
+    assert_eq!(
+        "=|a-cy - 12".parse(),
+        Ok(MetricsKey::Glyph {
+            name: "a-cy".to_string(),
+            opposite: true,
+            offset: -12.
+        })
+    );
+    assert!("n".parse::<MetricsKey>().is_err());
+}
+
+/// How far the outlines move right and what the advance width becomes, given the current width,
+/// the outlines' left and right, and whichever of the width and sidebearings have keys. Moving the
+/// outlines for the left sidebearing takes the width along with them, unless it has a key of its own.
+fn keyed_metrics(
+    width: f32,
+    bounds: Option<(f32, f32)>,
+    width_target: Option<f32>,
+    lsb_target: Option<f32>,
+    rsb_target: Option<f32>,
+) -> (f32, f32) {
+    let mut delta = 0.;
+    let mut width = width_target.unwrap_or(width);
+    if let (Some(lsb), Some((left, right))) = (lsb_target, bounds) {
+        delta = (lsb - left).round();
+        if let Some(rsb) = rsb_target {
+            width = right + delta + rsb;
+        } else if width_target.is_none() {
+            width += delta;
+        }
+    } else if let (Some(rsb), Some((_, right))) = (rsb_target, bounds) {
+        width = right + rsb;
+    }
+    (delta, width)
+}
+
+#[test]
+fn keyed_metrics_test() {
+    let bounds = Some((30., 470.));
+    assert_eq!(keyed_metrics(500., bounds, None, Some(50.), None), (20., 520.));
+    assert_eq!(keyed_metrics(500., bounds, Some(600.), Some(50.), None), (20., 600.));
+    assert_eq!(keyed_metrics(500., bounds, None, Some(50.), Some(40.)), (20., 530.));
+    assert_eq!(keyed_metrics(500., bounds, None, None, Some(40.)), (0., 510.));
+    assert_eq!(keyed_metrics(500., None, Some(600.), None, None), (0., 600.));
+}
+
+/// The horizontal metrics of a glyph other than the one being edited.
+struct SiblingMetrics {
+    lsb: f32,
+    rsb: f32,
+    width: f32,
+}
+
+impl Editor {
+    pub fn metrics_key(&self, side: MetricsKeySide) -> Option<String> {
+        self.with_glyph(|glyph| match &glyph.lib {
+            Lib::Plist(dict) => dict
+                .get(side.lib_key())
+                .and_then(|v| v.as_string())
+                .map(|s| s.to_string()),
+            _ => None,
+        })
+    }
+
+    /// Sets or, with None, removes a metrics key. Keys are validated before they're stored.
+    pub fn set_metrics_key(
+        &mut self,
+        side: MetricsKeySide,
+        key: Option<String>,
+    ) -> Result<(), String> {
+        if let Some(key) = key.as_ref() {
+            key.parse::<MetricsKey>()?;
+        }
+
+        self.begin_modification("Set metrics key.", false);
+        self.with_glyph_mut(|glyph| {
+            if !matches!(glyph.lib, Lib::Plist(_)) {
+                glyph.lib = Lib::Plist(plist::Dictionary::new());
+            }
+            if let Lib::Plist(dict) = &mut glyph.lib {
+                match key {
+                    Some(key) => {
+                        dict.insert(side.lib_key().to_string(), plist::Value::String(key));
+                    }
+                    None => {
+                        dict.remove(side.lib_key());
+                    }
+                }
+            }
+        });
+        self.end_modification();
+
+        Ok(())
+    }
+
+    fn sibling_metrics(&self, name: &str) -> Result<SiblingMetrics, String> {
+        if self.glyphs_dir().is_none() {
+            return Err("Metrics keys can only be resolved when editing a glyph in a UFO.".to_string());
+        }
+        let glif = self.read_sibling(name)?;
+
+        let bounds = glif
+            .outline
+            .as_ref()
+            .map(|o| o.to_skia_paths(None).combined())
+            .filter(|p| p.count_points() != 0)
+            .map(|p| p.compute_tight_bounds())
+            .ok_or_else(|| format!("Glyph {} has no outlines", name))?;
+        let width = glif.width.unwrap_or(0) as f32;
+
+        Ok(SiblingMetrics {
+            lsb: bounds.left,
+            rsb: width - bounds.right,
+            width,
+        })
+    }
+
+    fn resolve_metrics_key(&self, side: MetricsKeySide) -> Result<Option<f32>, String> {
+        let key: MetricsKey = match self.metrics_key(side) {
+            Some(key) => key.parse()?,
+            None => return Ok(None),
+        };
+
+        match key {
+            MetricsKey::Value(value) => Ok(Some(value)),
+            MetricsKey::Glyph {
+                name,
+                opposite,
+                offset,
+            } => {
+                let sibling = self.sibling_metrics(&name)?;
+                let value = match (side, opposite) {
+                    (MetricsKeySide::Left, false) | (MetricsKeySide::Right, true) => sibling.lsb,
+                    (MetricsKeySide::Right, false) | (MetricsKeySide::Left, true) => sibling.rsb,
+                    (MetricsKeySide::Width, false) => sibling.width,
+                    (MetricsKeySide::Width, true) => {
+                        return Err("A width metrics key can't use the opposite side.".to_string())
+                    }
+                };
+                Ok(Some(value + offset))
+            }
+        }
+    }
+
+    /// Resolves all of this glyph's metrics keys and applies them as one undoable change.
+    pub fn update_metrics_from_keys(&mut self) -> Result<(), String> {
+        let width_target = self.resolve_metrics_key(MetricsKeySide::Width)?;
+        let lsb_target = self.resolve_metrics_key(MetricsKeySide::Left)?;
+        let rsb_target = self.resolve_metrics_key(MetricsKeySide::Right)?;
+
+        if width_target.is_none() && lsb_target.is_none() && rsb_target.is_none() {
+            return Ok(());
+        }
+
+        let bounds = self.outline_bounds();
+        if bounds.is_none() && (lsb_target.is_some() || rsb_target.is_some()) {
+            return Err("Can't set sidebearings of a glyph with no outlines.".to_string());
+        }
+
+        let bounds = bounds.map(|b| (b.left, b.right));
+        let (delta, width) = keyed_metrics(
+            self.advance_width() as f32,
+            bounds,
+            width_target,
+            lsb_target,
+            rsb_target,
+        );
+        self.begin_modification("Update metrics from keys.", false);
+        if delta != 0. {
+            util::move_all_layers(self, -delta, f32::NAN);
+        }
+        self.with_glyph_mut(|glyph| glyph.width = Some(width.round().max(0.) as u64));
+        self.add_width_guidelines();
+        self.end_modification();
+
+        Ok(())
+    }
+}
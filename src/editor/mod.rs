@@ -12,23 +12,34 @@ use glifparser::{
 pub use skia_safe::Contains as _;
 pub use skia_safe::{Canvas, Matrix, Path as SkPath, Point as SkPoint, Rect as SkRect};
 
+use std::cell::RefCell;
 use std::collections::HashSet;
 use std::path;
 use std::sync::mpsc::{Receiver, Sender};
 
 use self::{history::History, selection::EditorClipboard};
 
+pub mod autotrace;
+pub mod clipboard;
 pub mod contour_handlers;
+pub mod contours;
 pub mod debug;
 pub mod events;
 pub mod filesystem_watch;
 pub mod headless;
 pub mod history;
+pub mod image_import;
 pub mod images;
 pub mod io;
 pub mod layers;
+pub mod metrics;
+pub mod metrics_keys;
+pub mod operation_stack;
 pub mod operations;
 pub mod selection;
+pub mod siblings;
+pub mod smart_guides;
+pub mod svg;
 pub mod tools;
 pub mod tunni;
 pub mod util;
@@ -60,9 +71,13 @@ pub struct Editor {
 
     pub(crate) filesystem_watch_tx: Sender<path::PathBuf>,
     pub(crate) filesystem_watch_rx: Receiver<path::PathBuf>,
+    // Linked pattern glyphs that changed while the glyph was being modified, reloaded once it's not.
+    pending_pattern_reloads: Vec<path::PathBuf>,
 
     preview_dirty: bool,
     pub preview: Option<MFEKGlif<MFEKPointData>>,
+    /// (contour, stage) of the active layer to only build through that stage of its operation stack.
+    pub operation_stack_preview: Option<(usize, usize)>,
 
     pub italic_angle: f32,
     pub selected: HashSet<(usize, usize)>,
@@ -74,6 +89,8 @@ pub struct Editor {
     pub quit_requested: bool, // allows for quits from outside event loop, e.g. from command closures
 
     pub ipc_info: Option<mfek_ipc::IPCInfo>,
+    // The glyph's file name and the UFO it's in, see font_dir.
+    font_dir_cache: RefCell<Option<(path::PathBuf, Option<path::PathBuf>)>>,
 }
 
 impl Editor {
@@ -102,13 +119,16 @@ impl Editor {
             guidelines: vec![],
             quit_requested: false,
             ipc_info: None,
+            font_dir_cache: RefCell::new(None),
             preview_dirty: true,
+            operation_stack_preview: None,
 
             tool_behaviors: vec![],
             behavior_finished: true,
 
             filesystem_watch_tx: fstx,
             filesystem_watch_rx: fsrx,
+            pending_pattern_reloads: vec![],
         };
         self_o.headless();
         self_o
@@ -0,0 +1,565 @@
+// Keeps contours' operation stacks (see contour_operations::stack). A contour's first operation is
+// its own, if glifparser can hold it; whether it's enabled, the operations after it, and anything
+// else glifparser can't hold about its own operation are kept in the glyph's lib under
+// "<layer index>/<contour index>". Since they're keyed by index, commands that remove or reorder
+// contours have to carry the stacks along with remap_operation_stacks (splice_sources helps), and
+// those that remove or reorder layers with remap_layer_operation_stacks.
+
+use glifparser::glif::contour_operations::{
+    dash::DashContour,
+    vws::{InterpolationType, VWSHandle},
+    ContourOperations,
+};
+use glifparser::glif::{contour::MFEKContourCommon as _, Lib, MFEKContour, MFEKGlif, MFEKOutline};
+use glifparser::{CapType, JoinType, MFEKPointData, VWSContour};
+use skia_safe::{PaintCap, PaintJoin};
+
+use super::Editor;
+use crate::contour_operations::dashalongpath::{build_dashes, DashOptions};
+use crate::contour_operations::nibstroke::NibContour;
+use crate::contour_operations::stack::{build_stack, Stage, StageOperation};
+use crate::contour_operations::variablewidthstroke::{interpolate_widths, WidthInterpolation};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const OPERATION_STACKS_LIB_KEY: &str = "org.mfek.operationStacks";
+
+fn key(layer: usize, contour: usize) -> String {
+    format!("{}/{}", layer, contour)
+}
+
+fn store_operation_stacks(glif: &mut MFEKGlif<MFEKPointData>, stacks: &plist::Dictionary) {
+    if !matches!(glif.lib, Lib::Plist(_)) {
+        glif.lib = Lib::Plist(plist::Dictionary::new());
+    }
+    if let Lib::Plist(lib) = &mut glif.lib {
+        if stacks.is_empty() {
+            lib.remove(OPERATION_STACKS_LIB_KEY);
+        } else {
+            lib.insert(OPERATION_STACKS_LIB_KEY.to_string(), plist::Value::Dictionary(stacks.clone()));
+        }
+    }
+}
+
+/// Where each contour came from after `removed` contours of `count` starting at `at` were replaced
+/// by `inserted` new ones, in the form remap_operation_stacks takes.
+pub fn splice_sources(count: usize, at: usize, removed: usize, inserted: usize) -> Vec<Option<usize>> {
+    (0..count - removed + inserted)
+        .map(|new| {
+            if new < at {
+                Some(new)
+            } else if new < at + inserted {
+                None
+            } else {
+                Some(new + removed - inserted)
+            }
+        })
+        .collect()
+}
+
+/// What's kept in the lib for one contour: whether its own operation is enabled, the stages after
+/// it, how the widths of its own variable width stroke change between points, which glyph its own
+/// pattern along path takes its pattern from, and its own dash's phase and fit.
+#[derive(Clone, Debug)]
+struct StackEntry {
+    first_enabled: bool,
+    stages: Vec<Stage>,
+    interpolation: Vec<WidthInterpolation>,
+    pattern_glyph: Option<String>,
+    dash: DashOptions,
+}
+
+impl Default for StackEntry {
+    fn default() -> Self {
+        Self {
+            first_enabled: true,
+            stages: vec![],
+            interpolation: vec![],
+            pattern_glyph: None,
+            dash: DashOptions::default(),
+        }
+    }
+}
+
+impl StackEntry {
+    fn from_plist(dict: &plist::Dictionary) -> Self {
+        let first_enabled = dict.get("firstEnabled").and_then(|v| v.as_boolean()).unwrap_or(true);
+        let stages = dict
+            .get("stages")
+            .and_then(|v| v.as_array())
+            .map(|stages| {
+                stages
+                    .iter()
+                    .filter_map(|stage| {
+                        let stage = stage.as_dictionary()?;
+                        let operation = match stage.get("nib").and_then(|v| v.as_dictionary()) {
+                            Some(nib) => StageOperation::Nib(NibContour::from_plist(nib)?),
+                            None => StageOperation::Contour(
+                                serde_json::from_str(stage.get("operation")?.as_string()?)
+                                    .map_err(|e| log::warn!("Skipping unreadable stacked operation: {}", e))
+                                    .ok()?,
+                            ),
+                        };
+                        let enabled = stage.get("enabled").and_then(|v| v.as_boolean()).unwrap_or(true);
+                        Some(Stage { operation, enabled })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let interpolation = dict
+            .get("widthInterpolation")
+            .and_then(|v| v.as_array())
+            .map(|modes| {
+                modes
+                    .iter()
+                    .map(|m| m.as_string().and_then(WidthInterpolation::from_name).unwrap_or_default())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let pattern_glyph = dict.get("patternGlyph").and_then(|v| v.as_string()).map(str::to_string);
+        let dash = dict
+            .get("dash")
+            .and_then(|v| v.as_dictionary())
+            .map(|dash| DashOptions {
+                phase: dash.get("phase").and_then(|v| v.as_real()).unwrap_or(0.) as f32,
+                fit_to_length: dash.get("fitToLength").and_then(|v| v.as_boolean()).unwrap_or(false),
+            })
+            .unwrap_or_default();
+        Self { first_enabled, stages, interpolation, pattern_glyph, dash }
+    }
+
+    fn to_plist(&self) -> plist::Value {
+        let mut dict = plist::Dictionary::new();
+        dict.insert("firstEnabled".to_string(), plist::Value::Boolean(self.first_enabled));
+        dict.insert(
+            "stages".to_string(),
+            plist::Value::Array(
+                self.stages
+                    .iter()
+                    .map(|stage| {
+                        let mut dict = plist::Dictionary::new();
+                        match &stage.operation {
+                            StageOperation::Contour(operation) => dict.insert(
+                                "operation".to_string(),
+                                plist::Value::String(serde_json::to_string(operation).unwrap()),
+                            ),
+                            StageOperation::Nib(nib) => dict.insert("nib".to_string(), nib.to_plist()),
+                        };
+                        dict.insert("enabled".to_string(), plist::Value::Boolean(stage.enabled));
+                        plist::Value::Dictionary(dict)
+                    })
+                    .collect(),
+            ),
+        );
+        if !self.linear() {
+            dict.insert(
+                "widthInterpolation".to_string(),
+                plist::Value::Array(
+                    self.interpolation
+                        .iter()
+                        .map(|m| plist::Value::String(m.name().to_string()))
+                        .collect(),
+                ),
+            );
+        }
+        if let Some(name) = &self.pattern_glyph {
+            dict.insert("patternGlyph".to_string(), plist::Value::String(name.clone()));
+        }
+        if !self.dash.is_default() {
+            let mut dash = plist::Dictionary::new();
+            dash.insert("phase".to_string(), plist::Value::Real(self.dash.phase as f64));
+            dash.insert("fitToLength".to_string(), plist::Value::Boolean(self.dash.fit_to_length));
+            dict.insert("dash".to_string(), plist::Value::Dictionary(dash));
+        }
+        plist::Value::Dictionary(dict)
+    }
+
+    fn linear(&self) -> bool {
+        self.interpolation.iter().all(|m| *m == WidthInterpolation::Linear)
+    }
+
+    fn is_default(&self) -> bool {
+        self.first_enabled
+            && self.stages.is_empty()
+            && self.linear()
+            && self.pattern_glyph.is_none()
+            && self.dash.is_default()
+    }
+}
+
+impl Editor {
+    fn operation_stacks_dict(&self) -> plist::Dictionary {
+        self.with_glyph(|glif| match &glif.lib {
+            Lib::Plist(lib) => lib
+                .get(OPERATION_STACKS_LIB_KEY)
+                .and_then(|v| v.as_dictionary())
+                .cloned()
+                .unwrap_or_default(),
+            _ => plist::Dictionary::new(),
+        })
+    }
+
+    fn set_operation_stacks_dict(&mut self, stacks: plist::Dictionary) {
+        self.with_glyph_mut(|glif| store_operation_stacks(glif, &stacks));
+    }
+
+    fn stack_entry(&self, li: usize, ci: usize) -> StackEntry {
+        self.operation_stacks_dict()
+            .get(&key(li, ci))
+            .and_then(|v| v.as_dictionary())
+            .map(StackEntry::from_plist)
+            .unwrap_or_default()
+    }
+
+    fn set_stack_entry(&mut self, li: usize, ci: usize, entry: StackEntry) {
+        let mut stacks = self.operation_stacks_dict();
+        if entry.is_default() {
+            stacks.remove(&key(li, ci));
+        } else {
+            stacks.insert(key(li, ci), entry.to_plist());
+        }
+        self.set_operation_stacks_dict(stacks);
+    }
+
+    /// The stack of a contour on the active layer as it goes on the clipboard, or None if it's
+    /// only its own operation. A piece of a contour keeps its stages, but not how its widths are
+    /// interpolated, as that's per point.
+    pub fn copied_stack_entry(&self, ci: usize, whole: bool) -> Option<plist::Value> {
+        let mut entry = self.stack_entry(self.layer_idx.unwrap(), ci);
+        if !whole {
+            entry.interpolation.clear();
+        }
+        (!entry.is_default()).then(|| entry.to_plist())
+    }
+
+    /// Gives a pasted contour on the active layer the stack it was copied with.
+    /// This function must be called between begin_modification and end_modification.
+    pub fn paste_stack_entry(&mut self, ci: usize, stack: &plist::Value) {
+        let entry = stack.as_dictionary().map(StackEntry::from_plist).unwrap_or_default();
+        self.set_stack_entry(self.layer_idx.unwrap(), ci, entry);
+    }
+
+    /// The full stack of a contour on the active layer, its own operation first.
+    pub fn operation_stack(&self, ci: usize) -> Vec<Stage> {
+        let li = self.layer_idx.unwrap();
+        let StackEntry { first_enabled, mut stages, .. } = self.stack_entry(li, ci);
+        let own = self.with_glyph(|glif| glif.layers[li].outline[ci].operation().clone());
+        if let Some(operation) = own {
+            stages.insert(0, Stage { operation: StageOperation::Contour(operation), enabled: first_enabled });
+        }
+        stages
+    }
+
+    /// Replaces the stack of a contour on the active layer. The first stage becomes the contour's
+    /// own operation, unless it's a nib stroke. This function must be called between
+    /// begin_modification and end_modification.
+    pub fn set_operation_stack(&mut self, ci: usize, mut stages: Vec<Stage>) {
+        let li = self.layer_idx.unwrap();
+        let own = match stages.first() {
+            Some(Stage { operation: StageOperation::Contour(operation), .. }) => Some(operation.clone()),
+            _ => None,
+        };
+        let first_enabled = if own.is_some() { stages.remove(0).enabled } else { true };
+
+        self.get_active_layer_mut().outline[ci].set_operation(own);
+
+        let mut entry = self.stack_entry(li, ci);
+        entry.first_enabled = first_enabled;
+        entry.stages = stages;
+        self.set_stack_entry(li, ci, entry);
+    }
+
+    /// How the widths of a contour's own variable width stroke change along each of its curves.
+    /// Curves past the end of it are linear.
+    pub fn width_interpolation(&self, ci: usize) -> Vec<WidthInterpolation> {
+        self.stack_entry(self.layer_idx.unwrap(), ci).interpolation
+    }
+
+    /// This function must be called between begin_modification and end_modification.
+    pub fn set_width_interpolation(&mut self, ci: usize, interpolation: Vec<WidthInterpolation>) {
+        let li = self.layer_idx.unwrap();
+        let mut entry = self.stack_entry(li, ci);
+        entry.interpolation = interpolation;
+        self.set_stack_entry(li, ci, entry);
+    }
+
+    /// The glyph in the UFO a contour's own pattern along path takes its pattern from, if it's
+    /// linked to one.
+    pub fn pattern_glyph(&self, ci: usize) -> Option<String> {
+        self.stack_entry(self.layer_idx.unwrap(), ci).pattern_glyph
+    }
+
+    /// This function must be called between begin_modification and end_modification.
+    pub fn set_pattern_glyph(&mut self, ci: usize, name: Option<String>) {
+        let li = self.layer_idx.unwrap();
+        let mut entry = self.stack_entry(li, ci);
+        entry.pattern_glyph = name;
+        self.set_stack_entry(li, ci, entry);
+    }
+
+    /// Where a contour's own dash starts along its pattern, and whether the pattern's fit to it.
+    pub fn dash_options(&self, ci: usize) -> DashOptions {
+        self.stack_entry(self.layer_idx.unwrap(), ci).dash
+    }
+
+    /// This function must be called between begin_modification and end_modification.
+    pub fn set_dash_options(&mut self, ci: usize, options: DashOptions) {
+        let li = self.layer_idx.unwrap();
+        let mut entry = self.stack_entry(li, ci);
+        entry.dash = options;
+        self.set_stack_entry(li, ci, entry);
+    }
+
+    /// Every contour, on any layer, whose pattern is linked to a glyph: (layer, contour, glyph).
+    fn pattern_links(&self) -> Vec<(usize, usize, String)> {
+        self.operation_stacks_dict()
+            .iter()
+            .filter_map(|(k, v)| {
+                let (li, ci) = k.split_once('/')?;
+                let name = StackEntry::from_plist(v.as_dictionary()?).pattern_glyph?;
+                Some((li.parse().ok()?, ci.parse().ok()?, name))
+            })
+            .collect()
+    }
+
+    /// Whether a pattern is linked to the glyph in the given file.
+    pub fn links_pattern_glyph(&self, path: &Path) -> bool {
+        self.pattern_links().iter().any(|(_, _, name)| {
+            self.sibling_path(name)
+                .map(|p| p.file_name() == path.file_name())
+                .unwrap_or(false)
+        })
+    }
+
+    /// Reads the patterns of contours linked to glyphs again, only of those linked to the glyph in
+    /// `changed` if given. Returns how many patterns were updated. It's not a modification, as the
+    /// patterns belong to the linked glyphs and there's nothing of this glyph's to undo.
+    pub fn refresh_pattern_glyphs(&mut self, changed: Option<&Path>) -> Result<usize, String> {
+        let mut updated = 0;
+        for (li, ci, name) in self.pattern_links() {
+            if let Some(changed) = changed {
+                if self.sibling_path(&name)?.file_name() != changed.file_name() {
+                    continue;
+                }
+            }
+            let pattern = self.sibling_pattern(&name)?;
+            updated += self.with_glyph_mut_no_history(|glif| {
+                let contour = match glif.layers.get_mut(li).and_then(|l| l.outline.get_mut(ci)) {
+                    Some(contour) => contour,
+                    None => return 0,
+                };
+                match contour.operation().clone() {
+                    Some(ContourOperations::PatternAlongPath { mut data }) => {
+                        data.pattern = pattern.clone();
+                        contour.set_operation(Some(ContourOperations::PatternAlongPath { data }));
+                        1
+                    }
+                    _ => 0,
+                }
+            });
+        }
+        if updated > 0 {
+            self.mark_preview_dirty();
+        }
+        Ok(updated)
+    }
+
+    /// Carries the active layer's stacks along after its contours were changed around. sources
+    /// gives, for each contour now in the layer, the index of the contour it came from, if any.
+    /// This function must be called between begin_modification and end_modification.
+    pub fn remap_operation_stacks(&mut self, sources: &[Option<usize>]) {
+        let li = self.layer_idx.unwrap();
+        let mut stacks = self.operation_stacks_dict();
+        let prefix = format!("{}/", li);
+        let old: HashMap<usize, plist::Value> = stacks
+            .keys()
+            .filter_map(|k| Some((k.strip_prefix(&prefix)?.parse().ok()?, k.clone())))
+            .collect::<Vec<(usize, String)>>()
+            .into_iter()
+            .filter_map(|(ci, k)| Some((ci, stacks.remove(&k)?)))
+            .collect();
+        for (new, source) in sources.iter().enumerate() {
+            if let Some(stack) = source.and_then(|s| old.get(&s)) {
+                stacks.insert(key(li, new), stack.clone());
+            }
+        }
+        self.set_operation_stacks_dict(stacks);
+        self.operation_stack_preview = None;
+    }
+
+    /// Carries every layer's stacks along when layers are removed or reordered; sources[new] is the
+    /// index the layer now at new had before. Layers are changed without a modification, so neither
+    /// is this.
+    pub fn remap_layer_operation_stacks(&mut self, sources: &[Option<usize>]) {
+        let old = self.operation_stacks_dict();
+        let mut stacks = plist::Dictionary::new();
+        for (new, source) in sources.iter().enumerate() {
+            if let Some(source) = source {
+                let prefix = format!("{}/", source);
+                for (k, stack) in old.iter() {
+                    if let Some(ci) = k.strip_prefix(&prefix) {
+                        stacks.insert(format!("{}/{}", new, ci), stack.clone());
+                    }
+                }
+            }
+        }
+        self.with_glyph_mut_no_history(|glif| store_operation_stacks(glif, &stacks));
+        self.operation_stack_preview = None;
+    }
+
+    /// Whether any contour has operations beyond its own.
+    pub fn has_stacked_operations(&self) -> bool {
+        !self.operation_stacks_dict().is_empty()
+    }
+
+    /// Removes every stack, e.g. when contour operations are turned off.
+    pub fn clear_operation_stacks(&mut self) {
+        self.set_operation_stacks_dict(plist::Dictionary::new());
+    }
+
+    /// Applies the stacks of the contours of a layer. If `staged` and operation_stack_preview is set, the
+    /// contour it names on the active layer is only built through that stage, so each stage can be looked at.
+    pub fn build_layer_operations(
+        &self,
+        li: usize,
+        outline: &MFEKOutline<MFEKPointData>,
+        staged: bool,
+    ) -> MFEKOutline<MFEKPointData> {
+        let stacks = self.operation_stacks_dict();
+        let mut ret = vec![];
+        for (ci, contour) in outline.iter().enumerate() {
+            if contour.len() <= 1 {
+                ret.push(contour.clone());
+                continue;
+            }
+            let entry = stacks
+                .get(&key(li, ci))
+                .and_then(|v| v.as_dictionary())
+                .map(StackEntry::from_plist)
+                .unwrap_or_default();
+            let through = match self.operation_stack_preview {
+                Some((pci, stage)) if staged && Some(li) == self.layer_idx && pci == ci => Some(stage),
+                _ => None,
+            };
+            ret.extend(build_contour_operations(contour, entry, through));
+        }
+        ret
+    }
+
+    /// The contours a contour of a layer becomes, its whole stack applied.
+    pub fn build_contour(&self, li: usize, ci: usize) -> MFEKOutline<MFEKPointData> {
+        let contour = self.with_glyph(|glif| glif.layers[li].outline[ci].clone());
+        if contour.len() <= 1 {
+            return vec![contour];
+        }
+        build_contour_operations(&contour, self.stack_entry(li, ci), None)
+    }
+
+    /// Replaces a contour on the active layer with the contours its operations make of it, so they
+    /// can be edited by hand. Returns how many contours it became, or None if it has no operations.
+    pub fn expand_contour_operations(&mut self, ci: usize) -> Option<usize> {
+        let li = self.layer_idx.unwrap();
+        let contour = self.get_active_layer_ref().outline[ci].clone();
+        if contour.len() <= 1 || self.operation_stack(ci).is_empty() {
+            return None;
+        }
+        let outline = build_contour_operations(&contour, self.stack_entry(li, ci), None);
+
+        let (count, built) = (self.get_active_layer_ref().outline.len(), outline.len());
+        let sources = splice_sources(count, ci, 1, built);
+
+        self.begin_modification("Expanded contour operations.", false);
+        let layer = self.get_active_layer_mut();
+        layer.outline.splice(ci..=ci, outline);
+        self.remap_operation_stacks(&sources);
+        self.end_modification();
+
+        self.operation_stack_preview = None;
+        self.contour_idx = None;
+        self.point_idx = None;
+        self.selected.clear();
+        Some(built)
+    }
+}
+
+/// Applies a contour's own operation and the stages after it, through stage `through` if given.
+fn build_contour_operations(
+    contour: &MFEKContour<MFEKPointData>,
+    entry: StackEntry,
+    through: Option<usize>,
+) -> MFEKOutline<MFEKPointData> {
+    let StackEntry { first_enabled, mut stages, .. } = entry.clone();
+    let mut contour = contour.clone();
+    match contour.operation().clone() {
+        // The dash's own stage is built here, and the rest of the stack applied to each dash.
+        Some(ContourOperations::DashAlongPath { data }) if first_enabled && !entry.dash.is_default() => {
+            let dashes = build_dashes(&data, &entry.dash, &contour);
+            return match through {
+                Some(0) => dashes,
+                _ => {
+                    let through = through.map(|t| t - 1);
+                    dashes.iter().flat_map(|dash| build_stack(dash, &stages, through)).collect()
+                }
+            };
+        }
+        Some(ContourOperations::VariableWidthStroke { data }) if first_enabled && !entry.linear() => {
+            let (split, data) = interpolate_widths(&contour, &data, &entry.interpolation);
+            contour = split;
+            let operation = ContourOperations::VariableWidthStroke { data };
+            stages.insert(0, Stage { operation: StageOperation::Contour(operation), enabled: true });
+        }
+        Some(operation) => {
+            stages.insert(0, Stage { operation: StageOperation::Contour(operation), enabled: first_enabled });
+        }
+        None => (),
+    }
+    build_stack(&contour, &stages, through)
+}
+
+/// The kinds of operation a stage can be added as from the inspector. Pattern along path needs a
+/// pattern, so it's set up with its tool instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NewStage {
+    Dash,
+    VariableWidthStroke,
+    Nib,
+}
+
+impl Editor {
+    /// A stage for a contour on the active layer, with the same defaults its tool would use.
+    pub fn new_stage(&self, kind: NewStage, ci: usize) -> Stage {
+        let operation = match kind {
+            NewStage::Nib => StageOperation::Nib(NibContour::default()),
+            NewStage::Dash => StageOperation::Contour(ContourOperations::DashAlongPath {
+                data: DashContour {
+                    stroke_width: 10.,
+                    cull: None,
+                    dash_desc: vec![10., 10.],
+                    include_last_path: false,
+                    paint_cap: PaintCap::Butt as u8,
+                    paint_join: PaintJoin::Miter as u8,
+                },
+            }),
+            NewStage::VariableWidthStroke => {
+                let handle = VWSHandle {
+                    left_offset: 10.,
+                    right_offset: 10.,
+                    interpolation: InterpolationType::Linear,
+                    tangent_offset: 0.,
+                };
+                StageOperation::Contour(ContourOperations::VariableWidthStroke {
+                    data: VWSContour {
+                        handles: vec![handle; self.get_active_layer_ref().outline[ci].len() + 1],
+                        cap_start_type: CapType::Round,
+                        cap_end_type: CapType::Round,
+                        join_type: JoinType::Round,
+                        remove_internal: false,
+                        remove_external: false,
+                    },
+                })
+            }
+        };
+        Stage { operation, enabled: true }
+    }
+}
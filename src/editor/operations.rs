@@ -1,4 +1,3 @@
-use crate::contour_operations::ContourOperationBuild;
 use crate::user_interface::Interface;
 use glifparser::glif::contour::MFEKContourCommon;
 use glifparser::outline::skia::{FromSkiaPath, ToSkiaPaths};
@@ -8,6 +7,7 @@ use glifparser::{
 };
 use glifparser::{FlattenedGlif, MFEKPointData};
 use skia_safe::PathOp;
+use std::borrow::Cow;
 use MFEKmath::mfek::ResolveCubic;
 
 use super::Editor;
@@ -23,6 +23,19 @@ impl Editor {
 
     pub fn rebuild(&mut self, i: &mut Interface) {
         self.handle_filesystem_events(i);
+        self.rebuild_preview();
+    }
+
+    /// Rebuilds the preview, if it's dirty. Unlike rebuild, this doesn't need an Interface, so it
+    /// can be used headless.
+    pub fn rebuild_preview(&mut self) {
+        // A stack's previewed through one of its stages only while its contour's the selected one.
+        if let Some((ci, _)) = self.operation_stack_preview {
+            if self.contour_idx != Some(ci) {
+                self.operation_stack_preview = None;
+                self.preview_dirty = true;
+            }
+        }
 
         if !self.preview_dirty {
             return;
@@ -33,28 +46,7 @@ impl Editor {
         }
 
         //self.fix_contour_ops();
-        let mut preview_layers = Vec::new();
-        for layer in &self.glyph.as_mut().unwrap().layers {
-            let mut preview_outline = Vec::new();
-
-            for (_idx, glif_contour) in layer.outline.iter().enumerate() {
-                if glif_contour.inner().len() <= 1 {
-                    preview_outline.push(glif_contour.to_cubic());
-                    continue;
-                }
-
-                let build_result: Vec<glifparser::glif::MFEKContour<MFEKPointData>> =
-                    glif_contour.operation().build(glif_contour);
-
-                for new_contour in build_result {
-                    preview_outline.push(new_contour.to_cubic());
-                }
-            }
-
-            let mut new_layer = layer.clone();
-            new_layer.outline = preview_outline;
-            preview_layers.push(new_layer);
-        }
+        let preview_layers = self.built_layers(true);
 
         let mut rects = Some(vec![]);
         let flattened = self.glyph.as_mut().unwrap().flattened(&mut rects);
@@ -70,6 +62,37 @@ impl Editor {
         self.preview_dirty = false;
     }
 
+    /// The glyph's layers with their contours' operations applied. If `staged`, a stack being
+    /// previewed through one of its stages is built only that far.
+    fn built_layers(&self, staged: bool) -> Vec<Layer<MFEKPointData>> {
+        let mut layers = Vec::new();
+        for (layer_idx, layer) in self.glyph.as_ref().unwrap().layers.iter().enumerate() {
+            let outline = self
+                .build_layer_operations(layer_idx, &layer.outline, staged)
+                .into_iter()
+                .map(|c| c.to_cubic())
+                .collect();
+
+            let mut new_layer = layer.clone();
+            new_layer.outline = outline;
+            layers.push(new_layer);
+        }
+        layers
+    }
+
+    /// The preview, unless it shows a stack built only partway, in which case the glyph is built
+    /// afresh with every stack in full. This, not the preview, is what's exported and measured.
+    pub fn built_glyph(&self) -> Cow<'_, MFEKGlif<MFEKPointData>> {
+        match self.preview.as_ref() {
+            Some(preview) if self.operation_stack_preview.is_none() => Cow::Borrowed(preview),
+            _ => {
+                let mut glyph = self.glyph.as_ref().unwrap().clone();
+                glyph.layers = self.built_layers(false);
+                Cow::Owned(glyph)
+            }
+        }
+    }
+
     pub fn prepare_export(&self) -> MFEKGlif<MFEKPointData> {
         let glyph = self
             .glyph
@@ -80,11 +103,12 @@ impl Editor {
                 .outline
                 .iter()
                 .all(|c| c.operation().clone() == None)
+            && !self.has_stacked_operations()
         {
             return glyph.clone();
         }
 
-        let glif = self.preview.as_ref().unwrap_or(glyph);
+        let glif = self.built_glyph();
 
         // MFEKGlif always has a layer zero so this is safe. (No it isn't, it can be invisible. TODO: Fix this.)
         let mut last_combine_layer: Layer<MFEKPointData> = glif.layers[0].clone();
@@ -139,7 +163,7 @@ impl Editor {
         combined_layer.outline = mfek_outline;
         exported_layers.push(combined_layer);
 
-        let mut exported_mfek = glif.clone();
+        let mut exported_mfek = glif.into_owned();
         exported_mfek.layers = exported_layers;
         exported_mfek
     }
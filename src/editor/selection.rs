@@ -10,9 +10,11 @@ use glifparser::{
 use MFEKmath::{Bezier, Evaluate, Rect, Vector};
 
 use arboard::{self, Clipboard};
-use serde_json;
 use shrinkwraprs;
 
+use super::clipboard::{looks_like_outlines, ClipboardFormat};
+use super::images::ImageSettings;
+use super::operation_stack::splice_sources;
 use super::Editor;
 use crate::user_interface::gui;
 
@@ -74,10 +76,19 @@ impl Editor {
         }
     }
 
-    /// Copy the current selection and put it in our clipboard.
+    /// Copy the current selection and put it in our clipboard, as GLIF other font editors can paste
+    /// with everything only we understand in its lib.
     pub fn copy_selection(&mut self) {
+        self.copy_selection_as(ClipboardFormat::Mfek);
+    }
+
+    /// Copy the current selection and put it in our clipboard, in a format other programs may
+    /// understand.
+    pub fn copy_selection_as(&mut self, format: ClipboardFormat) {
         let layer = &self.glyph.as_ref().unwrap().layers[self.layer_idx.unwrap()];
         let mut new_outline: Vec<MFEKContour<MFEKPointData>> = Vec::new();
+        // For each copied contour, the contour it was copied from and whether it was all copied.
+        let mut sources = Vec::new();
         for (contour_idx, contour) in layer.outline.iter().enumerate() {
             let mut results = Vec::new();
             let mut begin = 0;
@@ -110,26 +121,39 @@ impl Editor {
                         result.inner_mut().set_open();
                     }
                     new_outline.push(result);
+                    sources.push((contour_idx, !deleted));
                 }
             }
         }
 
-        let mut cliptext = String::from("text/vnd.mfek.glifjson\t");
-
-        cliptext.push_str(
-            std::str::from_utf8(
-                &serde_json::to_vec_pretty(&Layer {
-                    name: "".to_string(),
-                    visible: true,
-                    color: None,
-                    outline: new_outline,
-                    operation: None,
-                    images: layer.images.clone(),
-                })
-                .unwrap(),
-            )
-            .unwrap(),
-        );
+        let layer = Layer {
+            name: "".to_string(),
+            visible: true,
+            color: None,
+            outline: new_outline,
+            operation: None,
+            images: if format == ClipboardFormat::Mfek {
+                layer.images.clone()
+            } else {
+                vec![]
+            },
+        };
+        let stacks: Vec<Option<plist::Value>> = sources
+            .into_iter()
+            .map(|(ci, whole)| self.copied_stack_entry(ci, whole))
+            .collect();
+        // The copied layer's images are all of the active layer's, in order.
+        let li = self.layer_idx.unwrap();
+        let image_settings: Vec<ImageSettings> = (0..layer.images.len())
+            .map(|ii| self.image_settings(li, ii))
+            .collect();
+        let cliptext = match self.clipboard_text(&layer, &stacks, &image_settings, format) {
+            Ok(t) => t,
+            Err(e) => {
+                gui::error!("Clipboard issue—couldn't copy! {}", e);
+                return;
+            }
+        };
 
         self.clipboard
             .with(|c| {
@@ -144,52 +168,28 @@ impl Editor {
     /// If `position` is provided, it means that the client is requesting that the layer outline be
     /// moved
     pub fn paste_selection(&mut self, position: Option<(f32, f32)>) {
-        let mut clipboard: Layer<_> = if let Some(data) = self.clipboard.with(|clipboard: &mut Clipboard| {
-            let cbtext; // [For borrow checker!]
-            let (mimetype, data) = match clipboard.get_text() {
-                Ok(t) => {
-                    // [For borrow checker!] Hold a handle to clipboard text so not dropped at end of match {} block.
-                    cbtext = t;
-                    match cbtext.split_once('\t') {
-                        Some((mt, d)) => {
-                            (mt, d)
-                        },
-                        None => {
-                            log::debug!("Tried to paste in a clipboard w/o tab (\\t) character");
-                            return Err(());
-                        },
-                    }
-                }
-                Err(e) => {
-                    gui::error!("Failed to paste! {:?}", &e);
-                    return Err(());
-                }
-            };
-
-            if mimetype != "text/vnd.mfek.glifjson" {
-                log::warn!("We must've misrecognized data w/tab (\\t) character as ours, aborting");
-                return Err(());
+        let cbtext = match self.clipboard.with(|clipboard| clipboard.get_text()) {
+            Some(Ok(t)) => t,
+            Some(Err(e)) => {
+                gui::error!("Failed to paste! {:?}", &e);
+                return;
             }
-
-            match serde_json::from_str(data) {
-                Ok(d) => Ok(d),
-                Err(e) => {
-                    gui::error!("Could not understand text/vnd.mfek.glifjson we think we produced. Mismatched MFEKglif versions running on same machine? {:?}", &e);
-                    Err(())
-                }
+            None => return,
+        };
+        let mut contents = match self.clipboard_contents(&cbtext) {
+            Ok(contents) => contents,
+            Err(e) if looks_like_outlines(&cbtext) => {
+                gui::error!("Couldn't paste: {}", e);
+                return;
             }
-        }) {
-            match data {
-                Ok(d) => d,
-                Err(()) => {
-                    return;
-                }
+            Err(e) => {
+                log::debug!("Clipboard text isn't outlines, not pasting it: {}", e);
+                return;
             }
-        } else {
-            return;
         };
 
-        log::debug!("Got layer {} from clipboard", &clipboard.name);
+        log::debug!("Got layer {} from clipboard", &contents.layer.name);
+        let clipboard = &mut contents.layer;
 
         self.begin_modification("Paste clipboard.", false);
         self.contour_idx = None;
@@ -213,6 +213,7 @@ impl Editor {
         }
 
         let mut new_selected = HashSet::new();
+        let first_pasted = layer.outline.len();
 
         for contour in clipboard.outline.iter_mut() {
             let cur_idx = layer.outline.len();
@@ -224,6 +225,16 @@ impl Editor {
 
         self.selected.extend(new_selected);
 
+        for (i, stack) in contents.stacks.iter().enumerate() {
+            if let Some(stack) = stack {
+                self.paste_stack_entry(first_pasted + i, stack);
+            }
+        }
+
+        if let Err(e) = self.paste_images(&contents) {
+            gui::error!("Couldn't paste images: {}", e);
+        }
+
         self.end_modification();
     }
 
@@ -390,6 +401,8 @@ impl Editor {
 
         let layer = &self.glyph.as_ref().unwrap().layers[self.layer_idx.unwrap()];
         let mut new_outline: Vec<MFEKContour<MFEKPointData>> = Vec::new();
+        // For each contour left, the one it was cut from.
+        let mut sources: Vec<Option<usize>> = Vec::new();
         for (contour_idx, contour) in layer.outline.iter().enumerate() {
             let mut results = Vec::new();
             let mut begin = 0;
@@ -425,10 +438,12 @@ impl Editor {
                         //result.inner.last_mut().unwrap().a = Handle::Colocated;
                     }
                     new_outline.push(result);
+                    sources.push(Some(contour_idx));
                 }
             }
         }
         self.get_active_layer_mut().outline = new_outline;
+        self.remap_operation_stacks(&sources);
 
         self.contour_idx = None;
         self.point_idx = None;
@@ -490,6 +505,7 @@ impl Editor {
     }
 
     pub fn merge_contours(&mut self, start_contour: usize, end_contour: usize) {
+        let contour_count = self.get_active_layer_ref().outline.len();
         let (cidx, pidx) = {
             let layer = self.get_active_layer_mut();
 
@@ -519,6 +535,7 @@ impl Editor {
 
             (selected, p_idx)
         };
+        self.remap_operation_stacks(&splice_sources(contour_count, start_contour, 1, 0));
 
         self.contour_idx = Some(cidx);
         self.point_idx = Some(pidx);
@@ -0,0 +1,302 @@
+// Access to the other glyphs in the UFO layer the current glyph lives in. Glyphs are looked up by
+// name through the layer's contents.plist, which saving a renamed glyph also keeps up to date.
+
+use glifparser::glif::{contour::MFEKContourCommon as _, MFEKGlif, MFEKOutline};
+use glifparser::{Glif, MFEKPointData};
+use mfek_ipc::IPCInfo;
+use plist;
+
+use super::Editor;
+use crate::contour_operations::ContourOperationBuild as _;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use xmltree::{Element, EmitterConfig};
+
+/// A file name for a glyph, as the UFO spec says to make one: characters some filesystems can't
+/// hold become _, capitals are followed by _ so names differing only in case don't clash on
+/// case-insensitive filesystems, and parts that are reserved names on Windows get a _ in front.
+/// taken holds the other file names in the layer, lowercased.
+pub fn glif_file_name(name: &str, taken: &HashSet<String>) -> String {
+    const ILLEGAL: &str = "\"*+/:<>?[\\]|";
+    const RESERVED: &[&str] = &[
+        "con", "prn", "aux", "clock$", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9",
+        "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+    ];
+    const SUFFIX: &str = ".glif";
+
+    let mut escaped = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_control() || ILLEGAL.contains(c) || (i == 0 && c == '.') {
+            escaped.push('_');
+        } else if c.is_uppercase() {
+            escaped.push(c);
+            escaped.push('_');
+        } else {
+            escaped.push(c);
+        }
+    }
+    let mut stem = escaped
+        .split('.')
+        .map(|part| {
+            if RESERVED.contains(&part.to_lowercase().as_str()) {
+                format!("_{}", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+    // Leave room for the suffix, and for a counter if it clashes.
+    while stem.len() > 255 - SUFFIX.len() - 15 {
+        stem.pop();
+    }
+
+    let file_name = format!("{}{}", stem, SUFFIX);
+    if !taken.contains(&file_name.to_lowercase()) {
+        return file_name;
+    }
+    (1..)
+        .map(|n| format!("{}{:015}{}", stem, n, SUFFIX))
+        .find(|f| !taken.contains(&f.to_lowercase()))
+        .unwrap()
+}
+
+#[test]
+fn glif_file_name_test() {
+    let taken = HashSet::new();
+    assert_eq!(glif_file_name("a", &taken), "a.glif");
+    assert_eq!(glif_file_name("A", &taken), "A_.glif");
+    assert_eq!(glif_file_name("a-cy.sc", &taken), "a-cy.sc.glif");
+    assert_eq!(glif_file_name(".notdef", &taken), "_notdef.glif");
+    assert_eq!(glif_file_name("con", &taken), "_con.glif");
+    assert_eq!(glif_file_name("a/b", &taken), "a_b.glif");
+    let taken: HashSet<String> = ["a.glif".to_string()].into_iter().collect();
+    assert_eq!(glif_file_name("a", &taken), "a000000000000001.glif");
+}
+
+/// Points the components of a glyph file that use the glyph old_name at new_name. The file's only
+/// rewritten if any did.
+fn rename_components(path: &Path, old_name: &str, new_name: &str) -> Result<(), String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut root = Element::parse(file).map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+    let mut renamed = false;
+    if let Some(outline) = root.get_mut_child("outline") {
+        for node in outline.children.iter_mut() {
+            if let Some(component) = node.as_mut_element().filter(|e| e.name == "component") {
+                if component.attributes.get("base").map(String::as_str) == Some(old_name) {
+                    component.attributes.insert("base".to_string(), new_name.to_string());
+                    renamed = true;
+                }
+            }
+        }
+    }
+    if !renamed {
+        return Ok(());
+    }
+    let file = fs::File::create(path).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    root.write_with_config(file, EmitterConfig::new().perform_indent(true))
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+impl Editor {
+    /// The UFO the current glyph is in, if any.
+    pub fn font_dir(&self) -> Option<PathBuf> {
+        if let Some(ipc_info) = self.ipc_info.as_ref() {
+            return ipc_info.font.clone();
+        }
+
+        // We only have ipc_info if MFEKmetadata is available, but we don't need it to find the UFO.
+        // That means looking around the filesystem, and we're asked every frame, so the answer is
+        // kept for as long as the glyph's file name stays the same.
+        let filename = self.with_glyph(|glyph| glyph.filename.clone())?;
+        let mut cache = self.font_dir_cache.borrow_mut();
+        match cache.as_ref() {
+            Some((cached, font)) if *cached == filename => font.clone(),
+            _ => {
+                let font = IPCInfo::from_glif_path("MFEKglif".to_string(), &filename).font;
+                *cache = Some((filename, font.clone()));
+                font
+            }
+        }
+    }
+
+    /// The glyphs directory (UFO layer) the current glyph is in, or None if we're not in a UFO.
+    pub fn glyphs_dir(&self) -> Option<PathBuf> {
+        self.font_dir()?;
+        self.with_glyph(|glyph| glyph.filename.as_ref()?.parent().map(|p| p.to_owned()))
+    }
+
+    /// The UFO's unitsPerEm, or 1000 if we're not in a UFO or it doesn't say.
+    pub fn units_per_em(&self) -> f32 {
+        self.font_dir()
+            .and_then(|font| plist::Value::from_file(font.join("fontinfo.plist")).ok())
+            .and_then(|fontinfo| fontinfo.into_dictionary())
+            .and_then(|fontinfo| {
+                let upm = fontinfo.get("unitsPerEm")?;
+                upm.as_real().or_else(|| upm.as_signed_integer().map(|i| i as f64))
+            })
+            .map(|upm| upm as f32)
+            .unwrap_or(1000.)
+    }
+
+    /// The glyph name to file name mapping of the current UFO layer.
+    pub fn sibling_contents(&self) -> Result<plist::Dictionary, String> {
+        let glyphs_dir = self
+            .glyphs_dir()
+            .ok_or_else(|| "Not editing a glyph in a UFO.".to_string())?;
+        plist::Value::from_file(glyphs_dir.join("contents.plist"))
+            .map_err(|e| format!("Failed to read contents.plist: {}", e))?
+            .into_dictionary()
+            .ok_or_else(|| "contents.plist is not a dictionary".to_string())
+    }
+
+    pub fn sibling_path(&self, name: &str) -> Result<PathBuf, String> {
+        let contents = self.sibling_contents()?;
+        let filename = contents
+            .get(name)
+            .and_then(|f| f.as_string())
+            .ok_or_else(|| format!("No glyph named {} in this UFO", name))?;
+        Ok(self.glyphs_dir().unwrap().join(filename))
+    }
+
+    pub fn read_sibling(&self, name: &str) -> Result<Glif<MFEKPointData>, String> {
+        glifparser::read_from_filename(self.sibling_path(name)?)
+            .map_err(|e| format!("Failed to read glyph {}: {:?}", name, e))
+    }
+
+    /// The outline of another glyph, with its contour operations applied, to use as a pattern.
+    pub fn sibling_pattern(&self, name: &str) -> Result<MFEKOutline<MFEKPointData>, String> {
+        if self.with_glyph(|glyph| glyph.name == name) {
+            return Err("A glyph can't be its own pattern.".to_string());
+        }
+        let glyph: MFEKGlif<MFEKPointData> = self.read_sibling(name)?.into();
+        let outline: MFEKOutline<MFEKPointData> = glyph
+            .layers
+            .first()
+            .map(|layer| layer.outline.iter().flat_map(|c| c.operation().build(c)).collect())
+            .unwrap_or_default();
+        if outline.is_empty() {
+            return Err(format!("Glyph {} has no outline to use as a pattern.", name));
+        }
+        Ok(outline)
+    }
+
+    /// Renames the glyph. In a UFO the name mustn't be another glyph's. The glyph's files keep
+    /// their old name until it's saved or exported; see sync_glyph_files.
+    pub fn rename_glyph(&mut self, name: &str) -> Result<(), String> {
+        if self.with_glyph(|glyph| glyph.name == name) {
+            return Ok(());
+        }
+        if self.glyphs_dir().is_some() {
+            let own = self.own_glif_file();
+            if let Some(file) = self.sibling_contents()?.get(name) {
+                if file.as_string() != Some(own.as_str()) {
+                    return Err(format!("There's already a glyph named {} in this UFO.", name));
+                }
+            }
+        }
+        self.begin_modification("Renamed glyph.", false);
+        self.with_glyph_mut(|glyph| glyph.name = name.to_string());
+        self.end_modification();
+        Ok(())
+    }
+
+    /// The name of the current glyph's .glif file, whether it's being edited as .glif or .glifjson.
+    fn own_glif_file(&self) -> String {
+        let glif = self.filename_or_panic().with_extension("glif");
+        glif.file_name().unwrap().to_string_lossy().into_owned()
+    }
+
+    /// Brings a renamed glyph's files in line with its name before it's written: its .glif and
+    /// .glifjson move to a file name made from the new name, contents.plist maps the new name to
+    /// it, and the components of the other glyphs in the layer that used the old name use the new.
+    pub fn sync_glyph_files(&mut self) -> Result<(), String> {
+        let glyphs_dir = match self.glyphs_dir() {
+            Some(glyphs_dir) => glyphs_dir,
+            None => return Ok(()),
+        };
+        let name = self.with_glyph(|glyph| glyph.name.clone());
+        let own = self.own_glif_file();
+        let mut contents = self.sibling_contents()?;
+        let old_name = match contents.iter().find(|(_, f)| f.as_string() == Some(own.as_str())) {
+            Some((old_name, _)) if *old_name != name => old_name.clone(),
+            _ => return Ok(()),
+        };
+        if contents.contains_key(&name) {
+            return Err(format!("There's already a glyph named {} in this UFO.", name));
+        }
+
+        contents.remove(&old_name);
+        let taken: HashSet<String> = contents
+            .values()
+            .filter_map(|f| Some(f.as_string()?.to_lowercase()))
+            .collect();
+        let file_name = glif_file_name(&name, &taken);
+        let new_glif = glyphs_dir.join(&file_name);
+        let old_glif = glyphs_dir.join(&own);
+        for ext in ["glif", "glifjson"] {
+            let from = old_glif.with_extension(ext);
+            if from.exists() {
+                fs::rename(&from, new_glif.with_extension(ext))
+                    .map_err(|e| format!("Failed to rename {:?}: {}", from, e))?;
+            }
+        }
+        let ext = self
+            .filename_or_panic()
+            .extension()
+            .map(|e| e.to_owned())
+            .unwrap_or_else(|| "glif".into());
+        self.with_glyph_mut_no_history(|glyph| glyph.filename = Some(new_glif.with_extension(&ext)));
+
+        for file in contents.values().filter_map(|f| f.as_string()) {
+            let path = glyphs_dir.join(file);
+            if let Err(e) = rename_components(&path, &old_name, &name) {
+                log::warn!("{}", e);
+            }
+        }
+        contents.insert(name, plist::Value::String(file_name));
+        plist::Value::Dictionary(contents)
+            .to_file_xml(glyphs_dir.join("contents.plist"))
+            .map_err(|e| format!("Failed to write contents.plist: {}", e))
+    }
+
+    /// Maps every Unicode codepoint used by another glyph in this UFO layer to that glyph's name.
+    /// This reads every glyph, so it's slow on big fonts; callers should cache it.
+    pub fn sibling_unicodes(&self) -> Result<HashMap<char, String>, String> {
+        let current = self.with_glyph(|glyph| glyph.name.clone());
+        Ok(glyph_unicodes(&self.glyphs_dir().unwrap_or_default(), &self.sibling_contents()?, &current))
+    }
+}
+
+/// The Unicode codepoints of one glyph file.
+pub fn unicodes_of(path: &Path) -> Result<Vec<char>, String> {
+    let glif: Glif<MFEKPointData> =
+        glifparser::read_from_filename(path).map_err(|e| format!("Failed to read {:?}: {:?}", path, e))?;
+    Ok(glif.unicode)
+}
+
+/// sibling_unicodes for the glyphs of a contents.plist other than skip. It doesn't need an Editor,
+/// so it can be run on another thread.
+pub fn glyph_unicodes(glyphs_dir: &Path, contents: &plist::Dictionary, skip: &str) -> HashMap<char, String> {
+    let mut ret = HashMap::new();
+    for (name, filename) in contents.iter() {
+        if name == skip {
+            continue;
+        }
+        let filename = match filename.as_string() {
+            Some(filename) => filename,
+            None => continue,
+        };
+        match unicodes_of(&glyphs_dir.join(filename)) {
+            Ok(unicodes) => {
+                for c in unicodes {
+                    ret.insert(c, name.clone());
+                }
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+    ret
+}
@@ -0,0 +1,194 @@
+// Smart guides are the temporary alignment lines drawn while a point is being dragged. They show
+// up whenever the dragged point shares an x or y coordinate (within SMART_GUIDE_TOLERANCE screen
+// pixels) with another on-curve point in the glyph, or with one of the metrics lines.
+
+use glifparser::glif::mfek::contour::MFEKContourCommon;
+use glifparser::{Guideline, MFEKPointData};
+use skia_safe::{Canvas, Font, Matrix, Paint, PaintStyle, Point as SkPoint};
+
+use super::Editor;
+use crate::constants::{SMART_GUIDE_TEXT_SIZE, SMART_GUIDE_TOLERANCE};
+use crate::settings;
+use crate::user_interface::RenderState;
+
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignmentAxis {
+    /// The dragged point has the same x coordinate as the target (a vertical guide).
+    X,
+    /// The dragged point has the same y coordinate as the target (a horizontal guide).
+    Y,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlignmentTarget {
+    Point((f32, f32)),
+    Metric(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alignment {
+    pub axis: AlignmentAxis,
+    /// The coordinate shared by the dragged point and the target, in glyph units.
+    pub value: f32,
+    pub target: AlignmentTarget,
+}
+
+/// Returns the axis a guideline runs along if it's horizontal or vertical. Slanted guidelines, like
+/// the italic bearings, can't be aligned with on a single coordinate so we skip them.
+fn guideline_axis(guideline: &Guideline<MFEKPointData>) -> Option<AlignmentAxis> {
+    let angle = f64::from(guideline.angle).rem_euclid(180.);
+    if angle.abs() < f64::EPSILON {
+        Some(AlignmentAxis::Y)
+    } else if (angle - 90.).abs() < f64::EPSILON {
+        Some(AlignmentAxis::X)
+    } else {
+        None
+    }
+}
+
+/// Finds every on-curve point and metrics line that `position` is aligned with. Points in `ignore`
+/// (usually the points being dragged along with `position`) are not considered.
+pub fn find_alignments(
+    v: &Editor,
+    i: &RenderState,
+    position: (f32, f32),
+    ignore: &HashSet<(usize, usize)>,
+) -> Vec<Alignment> {
+    let tolerance = SMART_GUIDE_TOLERANCE / i.viewport.factor;
+    let mut ret = vec![];
+
+    for (ci, contour) in v.get_active_layer_ref().outline.iter().enumerate() {
+        for pi in 0..contour.len() {
+            if ignore.contains(&(ci, pi)) {
+                continue;
+            }
+            let point = contour.get_point(pi).unwrap();
+            let (px, py) = point.get_position();
+
+            if (px - position.0).abs() <= tolerance {
+                ret.push(Alignment {
+                    axis: AlignmentAxis::X,
+                    value: px,
+                    target: AlignmentTarget::Point((px, py)),
+                });
+            }
+            if (py - position.1).abs() <= tolerance {
+                ret.push(Alignment {
+                    axis: AlignmentAxis::Y,
+                    value: py,
+                    target: AlignmentTarget::Point((px, py)),
+                });
+            }
+        }
+    }
+
+    let mut metrics = vec![(AlignmentAxis::Y, 0., "baseline".to_string())];
+    let local_guidelines = v.with_glyph(|glyph| glyph.guidelines.clone());
+    for guideline in v.guidelines.iter().chain(local_guidelines.iter()) {
+        let name = guideline
+            .name
+            .clone()
+            .unwrap_or_else(|| "guideline".to_string());
+        match guideline_axis(guideline) {
+            Some(AlignmentAxis::X) => metrics.push((AlignmentAxis::X, guideline.at.x, name)),
+            Some(AlignmentAxis::Y) => metrics.push((AlignmentAxis::Y, guideline.at.y, name)),
+            None => {}
+        }
+    }
+
+    for (axis, value, name) in metrics {
+        let coord = match axis {
+            AlignmentAxis::X => position.0,
+            AlignmentAxis::Y => position.1,
+        };
+        if (value - coord).abs() <= tolerance {
+            ret.push(Alignment {
+                axis,
+                value,
+                target: AlignmentTarget::Metric(name),
+            });
+        }
+    }
+
+    ret
+}
+
+/// Draws the smart guides for the point currently being dragged, if any.
+pub fn draw_smart_guides(v: &Editor, i: &RenderState, canvas: &Canvas) {
+    let (vci, vpi) = match v.selected_point() {
+        Some(sp) => sp,
+        None => return,
+    };
+    let position = match v.get_active_layer_ref().outline[vci].get_point(vpi) {
+        Some(point) => point.get_position(),
+        None => return,
+    };
+
+    let mut ignore = v.selected.clone();
+    ignore.insert((vci, vpi));
+
+    let alignments = find_alignments(v, i, position, &ignore);
+    if alignments.is_empty() {
+        return;
+    }
+
+    let factor = i.viewport.factor;
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(settings::current().colors.smart_guide);
+    paint.set_style(PaintStyle::Stroke);
+    paint.set_stroke_width(1. / factor);
+
+    let mut labelled = HashSet::new();
+    for alignment in &alignments {
+        let (from, to) = match (&alignment.target, alignment.axis) {
+            (AlignmentTarget::Point(target), AlignmentAxis::X) => {
+                ((alignment.value, position.1), (alignment.value, target.1))
+            }
+            (AlignmentTarget::Point(target), AlignmentAxis::Y) => {
+                ((position.0, alignment.value), (target.0, alignment.value))
+            }
+            // Metrics lines are drawn already, so we only highlight the part near the point.
+            (AlignmentTarget::Metric(_), AlignmentAxis::X) => (
+                (alignment.value, position.1 - 50. / factor),
+                (alignment.value, position.1 + 50. / factor),
+            ),
+            (AlignmentTarget::Metric(_), AlignmentAxis::Y) => (
+                (position.0 - 50. / factor, alignment.value),
+                (position.0 + 50. / factor, alignment.value),
+            ),
+        };
+        canvas.draw_line(from, to, &paint);
+
+        let label = match (&alignment.target, alignment.axis) {
+            (AlignmentTarget::Metric(name), _) => format!("{} {}", name, alignment.value),
+            (AlignmentTarget::Point(_), AlignmentAxis::X) => format!("x {}", alignment.value),
+            (AlignmentTarget::Point(_), AlignmentAxis::Y) => format!("y {}", alignment.value),
+        };
+        if labelled.insert(label.clone()) {
+            let offset = labelled.len() as f32 * SMART_GUIDE_TEXT_SIZE * 1.25;
+            let at = (position.0 + 8. / factor, position.1 - offset / factor);
+            draw_label(canvas, &label, at, factor);
+        }
+    }
+}
+
+fn draw_label(canvas: &Canvas, text: &str, position: (f32, f32), factor: f32) {
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(settings::current().colors.smart_guide);
+
+    let mut font = Font::default();
+    font.set_size(SMART_GUIDE_TEXT_SIZE / factor);
+
+    // Our canvas has its y axis flipped, so we have to flip text back or it draws upside down.
+    let mut transform_matrix = Matrix::new_identity();
+    transform_matrix.pre_translate(SkPoint::from(position));
+    transform_matrix.pre_scale((1.0, -1.0), None);
+    canvas.save();
+    canvas.concat(&transform_matrix);
+    canvas.draw_str(text, SkPoint::from((0., 0.)), &font, &paint);
+    canvas.restore();
+}
@@ -0,0 +1,306 @@
+// Imports SVG outlines into the active layer, and exports the active layer as an SVG document.
+// Paths, rects, circles, ellipses, lines, polylines and polygons are understood, along with the
+// transforms on them and their groups. Everything else, like styles and text, is ignored.
+
+use glifparser::glif::MFEKContour;
+use glifparser::outline::skia::ToSkiaPaths as _;
+use glifparser::outline::FromKurbo as _;
+use glifparser::{MFEKPointData, Outline};
+use kurbo::{Affine, BezPath, PathEl, Point, Rect, Shape as _};
+use regex::Regex;
+use skia_safe::Matrix;
+use xmltree::{Element, XMLNode};
+
+use super::Editor;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// Kurbo approximates curved shapes with Béziers to within this many SVG units.
+const SHAPE_TOLERANCE: f64 = 0.01;
+
+fn number(element: &Element, name: &str) -> f64 {
+    element
+        .attributes
+        .get(name)
+        .and_then(|n| n.trim().trim_end_matches("px").parse().ok())
+        .unwrap_or(0.)
+}
+
+fn numbers(s: &str) -> Vec<f64> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+/// The part of the SVG's coordinate space that's shown: its viewBox, or else its width and height.
+pub fn svg_viewport(root: &Element) -> Option<Rect> {
+    let view_box = root.attributes.get("viewBox").map(|vb| numbers(vb)).unwrap_or_default();
+    let ret = if view_box.len() == 4 {
+        Rect::new(view_box[0], view_box[1], view_box[0] + view_box[2], view_box[1] + view_box[3])
+    } else {
+        Rect::new(0., 0., number(root, "width"), number(root, "height"))
+    };
+    if ret.width() > 0. && ret.height() > 0. {
+        Some(ret)
+    } else {
+        None
+    }
+}
+
+lazy_static! {
+    static ref TRANSFORM_RE: Regex = Regex::new(r"(\w+)\s*\(([^)]*)\)").unwrap();
+}
+
+fn parse_transform(transform: &str) -> Affine {
+    let mut ret = Affine::IDENTITY;
+    for cap in TRANSFORM_RE.captures_iter(transform) {
+        let args = numbers(&cap[2]);
+        let arg = |i: usize| args.get(i).copied();
+        let t = match (&cap[1], args.len()) {
+            ("matrix", 6) => Affine::new([args[0], args[1], args[2], args[3], args[4], args[5]]),
+            ("translate", 1 | 2) => Affine::translate((args[0], arg(1).unwrap_or(0.))),
+            ("scale", 1 | 2) => Affine::scale_non_uniform(args[0], arg(1).unwrap_or(args[0])),
+            ("rotate", 1) => Affine::rotate(args[0].to_radians()),
+            ("rotate", 3) => {
+                let center = (args[1], args[2]);
+                Affine::translate(center)
+                    * Affine::rotate(args[0].to_radians())
+                    * Affine::translate((-center.0, -center.1))
+            }
+            ("skewX", 1) => Affine::new([1., 0., args[0].to_radians().tan(), 1., 0., 0.]),
+            ("skewY", 1) => Affine::new([1., args[0].to_radians().tan(), 0., 1., 0., 0.]),
+            (name, _) => {
+                log::warn!("Ignoring invalid SVG transform {}({})", name, &cap[2]);
+                Affine::IDENTITY
+            }
+        };
+        ret = ret * t;
+    }
+    ret
+}
+
+fn points_path(points: &str, close: bool) -> BezPath {
+    let coords = numbers(points);
+    let mut ret = BezPath::new();
+    for (i, p) in coords.chunks_exact(2).enumerate() {
+        if i == 0 {
+            ret.move_to((p[0], p[1]));
+        } else {
+            ret.line_to((p[0], p[1]));
+        }
+    }
+    if close {
+        ret.close_path();
+    }
+    ret
+}
+
+fn element_path(element: &Element) -> Option<BezPath> {
+    let n = |name| number(element, name);
+    match element.name.as_str() {
+        "path" => {
+            let d = element.attributes.get("d")?;
+            BezPath::from_svg(d)
+                .map_err(|e| log::warn!("Skipping SVG path with invalid data: {:?}", e))
+                .ok()
+        }
+        "rect" => {
+            let rect = Rect::new(n("x"), n("y"), n("x") + n("width"), n("y") + n("height"));
+            // SVG allows different horizontal and vertical radii, which we don't.
+            let radius = if n("rx") > 0. { n("rx") } else { n("ry") };
+            Some(if radius > 0. {
+                rect.to_rounded_rect(radius).to_path(SHAPE_TOLERANCE)
+            } else {
+                rect.to_path(SHAPE_TOLERANCE)
+            })
+        }
+        "circle" => Some(kurbo::Circle::new((n("cx"), n("cy")), n("r")).to_path(SHAPE_TOLERANCE)),
+        "ellipse" => Some(
+            kurbo::Ellipse::new((n("cx"), n("cy")), (n("rx"), n("ry")), 0.).to_path(SHAPE_TOLERANCE),
+        ),
+        "line" => Some(points_path(
+            &format!("{} {} {} {}", n("x1"), n("y1"), n("x2"), n("y2")),
+            false,
+        )),
+        "polyline" => Some(points_path(element.attributes.get("points")?, false)),
+        "polygon" => Some(points_path(element.attributes.get("points")?, true)),
+        _ => None,
+    }
+}
+
+fn collect_paths(element: &Element, transform: Affine, out: &mut BezPath) {
+    // Nothing in these is drawn by itself.
+    if ["defs", "clipPath", "mask", "symbol", "marker", "pattern"].contains(&element.name.as_str()) {
+        return;
+    }
+    let transform = match element.attributes.get("transform") {
+        Some(t) => transform * parse_transform(t),
+        None => transform,
+    };
+    if let Some(mut path) = element_path(element) {
+        path.apply_affine(transform);
+        out.extend(path.elements().iter().copied());
+    }
+    for child in &element.children {
+        if let XMLNode::Element(child) = child {
+            collect_paths(child, transform, out);
+        }
+    }
+}
+
+/// Our outlines are cubic, so quadratic segments are raised to cubics.
+fn to_cubic(path: &BezPath) -> BezPath {
+    let mut ret = BezPath::new();
+    let (mut start, mut last) = (Point::ZERO, Point::ZERO);
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                start = p;
+                last = p;
+                ret.move_to(p);
+            }
+            PathEl::LineTo(p) => {
+                last = p;
+                ret.line_to(p);
+            }
+            PathEl::QuadTo(p1, p2) => {
+                ret.curve_to(last.lerp(p1, 2. / 3.), p2.lerp(p1, 2. / 3.), p2);
+                last = p2;
+            }
+            PathEl::CurveTo(p1, p2, p3) => {
+                last = p3;
+                ret.curve_to(p1, p2, p3);
+            }
+            PathEl::ClosePath => {
+                last = start;
+                ret.close_path();
+            }
+        }
+    }
+    ret
+}
+
+/// Reads every shape in an SVG document into one path, in the SVG's own (y down) coordinates,
+/// along with its viewport if it has one.
+pub fn parse_svg(data: &[u8]) -> Result<(BezPath, Option<Rect>), String> {
+    let root = Element::parse(data).map_err(|e| format!("Invalid SVG: {}", e))?;
+    let mut path = BezPath::new();
+    collect_paths(&root, Affine::IDENTITY, &mut path);
+    if path.elements().is_empty() {
+        return Err("SVG has no outlines to import.".to_string());
+    }
+    Ok((to_cubic(&path), svg_viewport(&root)))
+}
+
+/// Reads SVG path data, as in a path's d attribute.
+pub fn parse_svg_path_data(d: &str) -> Result<BezPath, String> {
+    BezPath::from_svg(d.trim())
+        .map(|path| to_cubic(&path))
+        .map_err(|e| format!("Invalid SVG path data: {:?}", e))
+}
+
+/// Turns an SVG path into contours, transforming it into glyph space on the way.
+pub fn svg_path_contours(mut path: BezPath, transform: Affine) -> Vec<MFEKContour<MFEKPointData>> {
+    path.apply_affine(transform);
+    let outline: Outline<MFEKPointData> = Outline::from_kurbo(&path);
+    outline.iter().map(|c| c.into()).collect()
+}
+
+pub fn read_svg(file: &Path) -> Result<(BezPath, Option<Rect>), String> {
+    let data = fs::read(file).map_err(|e| format!("Couldn't read {}: {}", file.display(), e))?;
+    parse_svg(&data).map_err(|e| format!("{}: {}", file.display(), e))
+}
+
+impl Editor {
+    /// Turns SVG outlines into contours. The SVG is scaled so its viewport's height is the font's
+    /// UPM, and its viewport's bottom left goes at position or, without one, on the descender at
+    /// the origin.
+    pub fn svg_contours(
+        &self,
+        path: BezPath,
+        viewport: Option<Rect>,
+        position: Option<(f32, f32)>,
+    ) -> Vec<MFEKContour<MFEKPointData>> {
+        let viewport = viewport.unwrap_or_else(|| path.bounding_box());
+        let scale = if viewport.height() > 0. {
+            self.units_per_em() as f64 / viewport.height()
+        } else {
+            1.
+        };
+        let position = position.unwrap_or((0., self.vertical_metric("descender").unwrap_or(0.)));
+
+        // SVG's y axis points down, ours up.
+        svg_path_contours(
+            path,
+            Affine::translate((position.0 as f64, position.1 as f64))
+                * Affine::scale_non_uniform(scale, -scale)
+                * Affine::translate((-viewport.x0, -viewport.y1)),
+        )
+    }
+
+    /// Adds the outlines of an SVG file to the active layer, placed as by svg_contours. Returns
+    /// how many contours were added. This function must be called between begin_modification and
+    /// end_modification.
+    pub fn import_svg(&mut self, file: &Path, position: Option<(f32, f32)>) -> Result<usize, String> {
+        let (path, viewport) = read_svg(file)?;
+        let contours = self.svg_contours(path, viewport, position);
+        let added = contours.len();
+        self.get_active_layer_mut().outline.extend(contours);
+        Ok(added)
+    }
+
+    /// An SVG document of the active layer, or just the selected contours. Like the exported
+    /// .glif, it's of the preview, so has the contours' operations applied.
+    pub fn export_svg(&self, selection_only: bool) -> String {
+        let li = self.layer_idx.unwrap();
+        let contours: Vec<MFEKContour<MFEKPointData>> = if selection_only {
+            let selected: HashSet<usize> = self.selected.iter().map(|(ci, _)| *ci).collect();
+            let mut selected: Vec<usize> = selected.into_iter().collect();
+            selected.sort();
+            selected.into_iter().flat_map(|ci| self.build_contour(li, ci)).collect()
+        } else {
+            self.built_glyph().layers[li].outline.clone()
+        };
+        self.svg_document(&contours)
+    }
+
+    /// An SVG document of contours. Its viewport is the advance width, from the descender to the
+    /// ascender.
+    pub fn svg_document(&self, contours: &[MFEKContour<MFEKPointData>]) -> String {
+        let mut path = contours.to_vec().to_skia_paths(None).combined();
+        path.transform(&Matrix::scale((1., -1.)));
+
+        let ascender = self.vertical_metric("ascender").unwrap_or(self.units_per_em());
+        let descender = self.vertical_metric("descender").unwrap_or(0.);
+        let width = match self.advance_width() {
+            0 => path.bounds().right.max(0.),
+            width => width as f32,
+        };
+        let height = ascender - descender;
+
+        format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 {y} {w} {h}\">\n",
+                "  <path d=\"{d}\"/>\n",
+                "</svg>\n"
+            ),
+            w = width,
+            h = height,
+            y = -ascender,
+            d = path.to_svg(),
+        )
+    }
+}
+
+#[test]
+fn parse_transform_test() {
+    let t = parse_transform("translate(10 20) scale(2)");
+    assert_eq!(t * Point::new(1., 1.), Point::new(12., 22.));
+    let t = parse_transform("rotate(90, 1, 1)");
+    let p = t * Point::new(2., 1.);
+    assert!((p.x - 1.).abs() < 1e-9 && (p.y - 2.).abs() < 1e-9);
+    assert_eq!(parse_transform("bogus(1)"), Affine::IDENTITY);
+}
@@ -6,7 +6,7 @@ use super::{events::EditorEvent, Editor};
 use crate::{
     tool_behaviors::ToolBehavior,
     tools::{tool_enum_to_tool, Tool, ToolEnum},
-    user_interface::Interface,
+    user_interface::{Interface, RenderState},
     util,
 };
 
@@ -31,7 +31,7 @@ impl Editor {
         }
     }
 
-    pub fn dispatch_tool_draw(&mut self, i: &Interface, canvas: &Canvas) {
+    pub fn dispatch_tool_draw(&mut self, i: &RenderState, canvas: &Canvas) {
         if let Some(behavior) = self.tool_behaviors.pop() {
             let mut active_behavior = dyn_clone::clone_box(&*behavior);
             active_behavior.draw(self, i, canvas);
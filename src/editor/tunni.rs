@@ -2,7 +2,7 @@ use flo_curves::Line;
 use glifparser::{glif::contour::MFEKContourCommon, MFEKPointData, Point};
 use MFEKmath::{vec2, Vector};
 
-use crate::{get_point, user_interface::Interface};
+use crate::{get_point, user_interface::RenderState};
 
 use super::Editor;
 
@@ -112,7 +112,7 @@ fn get_distance_from_tunni_line(pos: Vector, line: (Vector, Vector)) -> f64 {
     return clamped_position.distance(pos);
 }
 
-pub fn get_closest_tunni_line(v: &Editor, i: &Interface) -> Option<TunniLineInfo> {
+pub fn get_closest_tunni_line(v: &Editor, i: &RenderState) -> Option<TunniLineInfo> {
     let mut closest_distance = f64::INFINITY;
     let mut closest_tunni: Option<TunniLineInfo> = None;
 
@@ -165,7 +165,7 @@ pub enum Tunni {
 
 const MIN_DISTANCE_FOR_CLICK: f64 = 5.;
 
-pub fn clicked_tunni_point_or_line(v: &Editor, i: &Interface) -> Option<(TunniLineInfo, Tunni)> {
+pub fn clicked_tunni_point_or_line(v: &Editor, i: &RenderState) -> Option<(TunniLineInfo, Tunni)> {
     let closest_point_or_line = get_closest_tunni_line(v, i);
 
     if let Some(tunni_info) = closest_point_or_line {
@@ -1,14 +1,15 @@
 // This file is mainly utilities that are common use cases for the editor, but don't necessarily need to be
 // in Editor.
 
-use crate::user_interface::Interface;
+use crate::user_interface::RenderState;
 use crate::{get_contour_len, get_point_mut};
 use flo_curves::{
     bezier::{solve_curve_for_t_along_axis, Curve as FloCurve},
     geo::Coord2,
 };
-use glifparser::WhichHandle;
+use glifparser::{IntegerOrFloat, WhichHandle};
 use glifrenderer::constants::{POINT_RADIUS, POINT_STROKE_THICKNESS};
+use kurbo::Affine;
 use skia_safe::Contains;
 use skia_safe::Point as SkPoint;
 use skia_safe::Rect as SkRect;
@@ -59,7 +60,7 @@ impl ClickedPoint<'_, '_, '_> {
 /// Utility function to quickly check which point or mouse is hovering. Optional mask parameter specifies a point to ignore.
 pub fn clicked_point_or_handle(
     v: &Editor,
-    i: &Interface,
+    i: &RenderState,
     position: (f32, f32),
     mask: Option<(usize, usize)>,
 ) -> Option<(usize, usize, WhichHandle)> {
@@ -155,7 +156,7 @@ pub struct HoveredPointInfo {
 
 pub fn nearest_point_on_curve(
     v: &Editor,
-    i: &Interface,
+    i: &RenderState,
     position: (f32, f32),
 ) -> Option<HoveredPointInfo> {
     {
@@ -228,25 +229,40 @@ pub fn nearest_point_on_curve(
     }
 }
 
+/// Moves everything in the glyph by (-x, -y): the outlines of every layer, as well as the anchors,
+/// components and images, so that spacing changes don't leave any of them behind. Either axis can
+/// be NaN, which is treated as zero.
 pub fn move_all_layers(v: &mut Editor, mut x: f32, mut y: f32) {
+    if x.is_nan() {
+        x = 0.;
+    }
+    if y.is_nan() {
+        y = 0.;
+    }
+
     v.with_glyph_mut(|glyph| {
         for li in 0..glyph.layers.len() {
             for ci in 0..glyph.layers[li].outline.len() {
                 for pi in 0..glyph.layers[li].outline[ci].inner().len() {
                     let point = get_point_mut!(glyph.layers[li], ci, pi).unwrap();
                     let (cx, cy) = point.get_position();
-
-                    if x.is_nan() {
-                        x = 0.;
-                    }
-                    if y.is_nan() {
-                        y = 0.;
-                    }
-
-                    let (x, y) = (cx - x, cy - y);
-                    point.set_position(x, y)
+                    point.set_position(cx - x, cy - y)
                 }
             }
+
+            for (_image, affine) in glyph.layers[li].images.iter_mut() {
+                *affine = Affine::translate((-x as f64, -y as f64)) * *affine;
+            }
+        }
+
+        for anchor in glyph.anchors.iter_mut() {
+            anchor.x -= x;
+            anchor.y -= y;
+        }
+
+        for component in glyph.components.vec.iter_mut() {
+            component.xOffset = IntegerOrFloat::Float(f32::from(component.xOffset) - x);
+            component.yOffset = IntegerOrFloat::Float(f32::from(component.yOffset) - y);
         }
     });
 }
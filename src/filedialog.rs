@@ -1,3 +1,4 @@
+use crate::editor::image_import::IMAGE_EXTENSIONS;
 use crate::util;
 
 use std::path::PathBuf;
@@ -17,7 +18,7 @@ pub fn filename_or_panic(
 }
 
 pub fn open_image(start_in: Option<&str>) -> Option<PathBuf> {
-    match nfd::open_file_dialog(Some("jpg,png,gif,webp,bmp"), start_in) {
+    match nfd::open_file_dialog(Some(&IMAGE_EXTENSIONS.join(",")), start_in) {
         Ok(nfd::Response::Okay(file)) => Some(file.into()),
         Ok(_) | Err(_) => None,
     }
@@ -13,6 +13,7 @@
 
 use crate::command::{Command, CommandInfo, CommandMod};
 use crate::editor::{
+    clipboard::ClipboardFormat,
     events::{EditorEvent, IOEventType, MouseEventType},
     Editor,
 };
@@ -62,6 +63,11 @@ fn main() {
 
     let filename = filedialog::filename_or_panic(&filename, Some("glif"), None);
     let mut interface = Interface::new(filename.to_str().unwrap());
+    match settings::load_settings() {
+        Ok(loaded) => settings::set_current(loaded),
+        Err(e) => log::warn!("{} Using default settings.", e),
+    }
+    settings::current().apply(&mut interface);
     let mut sk_surface = interface.create_surface();
     let mut window_manager = WindowManager::new();
     let mut egui_manager = EguiManager::new(&mut interface);
@@ -94,6 +100,13 @@ fn main() {
                 editor.quit(&mut interface);
             }
 
+            // Keys being recorded for a binding go nowhere else.
+            if let Event::KeyDown { keycode: Some(keycode), .. } = &event {
+                if command::record_keypress(keycode, &keys_down) {
+                    continue;
+                }
+            }
+
             if egui_manager.wants_event(
                 interface.windows().into_iter().nth(0).unwrap().sdl_window,
                 &event,
@@ -168,6 +181,9 @@ fn main() {
                         Command::ToolVWS => {
                             editor.set_tool(ToolEnum::VWS);
                         }
+                        Command::ToolNib => {
+                            editor.set_tool(ToolEnum::Nib);
+                        }
                         Command::ToolMeasure => {
                             editor.set_tool(ToolEnum::Measure);
                         }
@@ -216,6 +232,12 @@ fn main() {
                         Command::CopySelection => {
                             editor.copy_selection();
                         }
+                        Command::CopySelectionAsGlif => {
+                            editor.copy_selection_as(ClipboardFormat::Glif);
+                        }
+                        Command::CopySelectionAsSvg => {
+                            editor.copy_selection_as(ClipboardFormat::Svg);
+                        }
                         Command::PasteSelection => {
                             editor.paste_selection(Some(interface.mouse_info.position));
                         }
@@ -300,6 +322,20 @@ fn main() {
                         Command::ReverseContour => {
                             log::debug!("Tried to reverse contour outside Select tool");
                         }
+                        Command::HarmonizeSelection => {
+                            editor.harmonize_selection(false);
+                        }
+                        Command::HarmonizeSelectionMovingPoints => {
+                            editor.harmonize_selection(true);
+                        }
+                        Command::BalanceSelection => {
+                            editor.balance_selection();
+                        }
+                        Command::ExpandContourOperations => {
+                            if let Some(ci) = editor.contour_idx {
+                                editor.expand_contour_operations(ci);
+                            }
+                        }
                         Command::SkiaDump => {
                             editor.skia_dump();
                         }
@@ -412,6 +448,10 @@ fn main() {
                     );
                 }
 
+                Event::DropFile { filename, .. } => {
+                    editor.drop_file(&mut interface, filename.into());
+                }
+
                 Event::Window { win_event, .. } => match win_event {
                     WindowEvent::SizeChanged(x, y) | WindowEvent::Resized(x, y) => {
                         interface.viewport.winsize = (x as f32, y as f32);
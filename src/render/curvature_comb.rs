@@ -0,0 +1,262 @@
+// The curvature comb draws, along each curve, teeth as long as the curvature there times a scale,
+// pointing away from the centre of curvature, and joins their tips. A smooth comb means smooth
+// curvature. Joins where the tangent is continuous but the curvature jumps (G1 but not G2) and
+// inflections, where the curvature changes sign, are marked, since those are where outlines tend
+// to look lumpy.
+
+use glifparser::glif::MFEKContour;
+use glifparser::MFEKPointData;
+use glifrenderer::toggles::PreviewMode;
+use skia_safe::{Canvas, Paint, PaintStyle, Path};
+use MFEKmath::{
+    mfek::ResolveCubic, ArcLengthParameterization, Bezier, Evaluate, Parameterization, Piecewise,
+    Vector,
+};
+
+use super::RenderState;
+use crate::constants::{
+    COMB_FLAT_CURVATURE, COMB_G1_ANGLE_TOLERANCE, COMB_G2_TOLERANCE, COMB_MARK_SIZE,
+};
+use crate::editor::Editor;
+use crate::settings;
+
+use std::collections::BTreeSet;
+
+pub struct CurvatureComb {
+    pub enabled: bool,
+    /// Draw the comb of every contour, rather than just of those with selected points.
+    pub all_contours: bool,
+    /// Teeth per curve segment.
+    pub density: usize,
+    /// How long, in font units, a tooth is for a curvature of one over a font unit.
+    pub scale: f32,
+    pub mark_discontinuities: bool,
+}
+
+impl Default for CurvatureComb {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            all_contours: false,
+            density: 32,
+            scale: 20000.,
+            mark_discontinuities: true,
+        }
+    }
+}
+
+// The derivative of a cubic Bézier is a quadratic with these control points…
+fn bezier_derivative(bez: &Bezier) -> (Vector, Vector, Vector) {
+    let dp0 = (bez.w2 - bez.w1) * 3.0;
+    let dp1 = (bez.w3 - bez.w2) * 3.0;
+    let dp2 = (bez.w4 - bez.w3) * 3.0;
+    (dp0, dp1, dp2)
+}
+
+// …and its second derivative is a line between these.
+fn bezier_second_derivative(bez: &Bezier) -> (Vector, Vector) {
+    let ddp0 = (bez.w3 - bez.w2 * 2.0 + bez.w1) * 6.0;
+    let ddp1 = (bez.w4 - bez.w3 * 2.0 + bez.w2) * 6.0;
+    (ddp0, ddp1)
+}
+
+/// Signed curvature at t, positive where the curve turns left, and the unit normal to its left.
+/// None where the curve has no direction, e.g. at the end of a retracted handle.
+fn curvature_at(bez: &Bezier, t: f64) -> Option<(f64, Vector)> {
+    let (d0, d1, d2) = bezier_derivative(bez);
+    let (dd0, dd1) = bezier_second_derivative(bez);
+    let mt = 1.0 - t;
+    let first = d0 * mt.powi(2) + d1 * 2.0 * mt * t + d2 * t.powi(2);
+    let second = dd0 * mt + dd1 * t;
+
+    let speed = (first.x.powi(2) + first.y.powi(2)).sqrt();
+    if speed < 1e-6 {
+        return None;
+    }
+    let curvature = (first.x * second.y - first.y * second.x) / speed.powi(3);
+    let normal = Vector {
+        x: -first.y / speed,
+        y: first.x / speed,
+    };
+    Some((curvature, normal))
+}
+
+/// Direction the curve leaves its start in, or arrives at its end in, looking past retracted
+/// handles.
+fn end_tangent(bez: &Bezier, at_end: bool) -> Option<Vector> {
+    let candidates = if at_end {
+        [bez.w4 - bez.w3, bez.w4 - bez.w2, bez.w4 - bez.w1]
+    } else {
+        [bez.w2 - bez.w1, bez.w3 - bez.w1, bez.w4 - bez.w1]
+    };
+    candidates
+        .into_iter()
+        .find(|v| v.x.abs() > 1e-6 || v.y.abs() > 1e-6)
+        .map(|v| v.normalize())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Mark {
+    Discontinuity(Vector),
+    Inflection(Vector),
+}
+
+/// Where curvature jumps or changes sign at the join of two segments.
+fn join_mark(before: &Bezier, after: &Bezier) -> Option<Mark> {
+    let (t0, t1) = (end_tangent(before, true)?, end_tangent(after, false)?);
+    // Corners are meant to be corners.
+    if t0.dot(t1) < COMB_G1_ANGLE_TOLERANCE.to_radians().cos() as f64 {
+        return None;
+    }
+    let (k0, _) = curvature_at(before, 1.)?;
+    let (k1, _) = curvature_at(after, 0.)?;
+    let largest = k0.abs().max(k1.abs());
+    if k0 * k1 < 0. && largest > COMB_FLAT_CURVATURE {
+        Some(Mark::Inflection(after.w1))
+    } else if (k0 - k1).abs() > largest * COMB_G2_TOLERANCE as f64 && largest > COMB_FLAT_CURVATURE {
+        Some(Mark::Discontinuity(after.w1))
+    } else {
+        None
+    }
+}
+
+impl CurvatureComb {
+    fn draw_segment(&self, bez: &Bezier, i: &RenderState, marks: &mut Vec<Mark>, canvas: &Canvas) {
+        let arclen_param = ArcLengthParameterization::from(bez, 100);
+        let mut tips = Path::new();
+        let mut teeth = Path::new();
+        let mut last: Option<(f64, f64)> = None;
+
+        for tooth in 0..=self.density {
+            let t = arclen_param.parameterize(tooth as f64 / self.density as f64);
+            let (curvature, normal) = match curvature_at(bez, t) {
+                Some(c) => c,
+                None => continue,
+            };
+            let point = bez.at(t);
+            let tip = point - normal * curvature * self.scale as f64;
+
+            teeth.move_to(point.to_skia_point());
+            teeth.line_to(tip.to_skia_point());
+            if last.is_none() {
+                tips.move_to(tip.to_skia_point());
+            } else {
+                tips.line_to(tip.to_skia_point());
+            }
+
+            if let Some((last_t, last_curvature)) = last {
+                if last_curvature * curvature < 0.
+                    && last_curvature.abs().max(curvature.abs()) > COMB_FLAT_CURVATURE
+                {
+                    marks.push(Mark::Inflection(bez.at(find_inflection(bez, last_t, t))));
+                }
+            }
+            last = Some((t, curvature));
+        }
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(1. / i.viewport.factor);
+        let colors = settings::current().colors;
+        paint.set_color(colors.comb_teeth);
+        canvas.draw_path(&teeth, &paint);
+        paint.set_color(colors.comb_outline);
+        canvas.draw_path(&tips, &paint);
+    }
+
+    fn draw_contour(
+        &self,
+        contour: &MFEKContour<MFEKPointData>,
+        i: &RenderState,
+        canvas: &Canvas,
+    ) {
+        let piecewise: Piecewise<Bezier> = contour.to_cubic().into();
+        let mut marks = vec![];
+        for bez in &piecewise.segs {
+            self.draw_segment(bez, i, &mut marks, canvas);
+        }
+
+        if !self.mark_discontinuities {
+            return;
+        }
+        let segs = &piecewise.segs;
+        for pair in segs.windows(2) {
+            marks.extend(join_mark(&pair[0], &pair[1]));
+        }
+        if let (Some(last), Some(first)) = (segs.last(), segs.first()) {
+            if segs.len() > 1 && (last.w4 - first.w1).magnitude() < 1e-6 {
+                marks.extend(join_mark(last, first));
+            }
+        }
+
+        let colors = settings::current().colors;
+        let radius = COMB_MARK_SIZE / i.viewport.factor;
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        for mark in marks {
+            match mark {
+                Mark::Discontinuity(at) => {
+                    paint.set_style(PaintStyle::Stroke);
+                    paint.set_stroke_width(2. / i.viewport.factor);
+                    paint.set_color(colors.comb_discontinuity);
+                    canvas.draw_circle(at.to_skia_point(), radius, &paint);
+                }
+                Mark::Inflection(at) => {
+                    paint.set_style(PaintStyle::Fill);
+                    paint.set_color(colors.comb_inflection);
+                    canvas.draw_circle(at.to_skia_point(), radius / 2., &paint);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, v: &Editor, i: &RenderState, canvas: &Canvas) {
+        if !self.enabled || i.viewport.preview_mode == PreviewMode::Paper {
+            return;
+        }
+
+        let layer = v.get_active_layer_ref();
+        let selected: BTreeSet<usize> = v
+            .selected
+            .iter()
+            .map(|(ci, _)| *ci)
+            .chain(v.contour_idx)
+            .collect();
+        for (ci, contour) in layer.outline.iter().enumerate() {
+            if self.all_contours || selected.contains(&ci) {
+                self.draw_contour(contour, i, canvas);
+            }
+        }
+    }
+}
+
+/// Narrows down where, between t0 and t1, the curvature changes sign.
+fn find_inflection(bez: &Bezier, mut t0: f64, mut t1: f64) -> f64 {
+    let sign = |t| curvature_at(bez, t).map(|(k, _)| k.signum()).unwrap_or(0.);
+    let start_sign = sign(t0);
+    for _ in 0..16 {
+        let mid = (t0 + t1) / 2.;
+        if sign(mid) == start_sign {
+            t0 = mid;
+        } else {
+            t1 = mid;
+        }
+    }
+    (t0 + t1) / 2.
+}
+
+#[test]
+fn curvature_at_test() {
+    // A quarter of a circle of radius 100, turning left.
+    let k = 0.5522847498 * 100.;
+    let bez = Bezier::from_points(
+        Vector { x: 100., y: 0. },
+        Vector { x: 100., y: k },
+        Vector { x: k, y: 100. },
+        Vector { x: 0., y: 100. },
+    );
+    let (curvature, normal) = curvature_at(&bez, 0.5).unwrap();
+    assert!((curvature - 0.01).abs() < 0.0005);
+    assert!(normal.x < 0. && normal.y < 0.);
+}
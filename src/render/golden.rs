@@ -0,0 +1,132 @@
+// Golden image tests: every .glif in examples/ is drawn through render_frame into an offscreen
+// surface and compared against a PNG in tests/golden. A missing golden image fails the test; if
+// MFEK_BLESS_GOLDEN is set, they're written instead, so after adding an example or an intended
+// change to rendering run:
+//
+//     MFEK_BLESS_GOLDEN=1 cargo test golden
+//
+// and commit the new images.
+
+use skia_safe::{self as skia, AlphaType, ColorType, EncodedImageFormat, ImageInfo};
+
+use super::{render_frame, RenderState};
+use crate::args::{Args, HeadlessMode};
+use crate::editor::Editor;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const GOLDEN_SIZE: (i32, i32) = (512, 512);
+// How far a channel may be off before a pixel counts as different, and how many pixels may differ,
+// so that small changes in Skia's antialiasing don't fail the test.
+const GOLDEN_CHANNEL_TOLERANCE: u8 = 2;
+const GOLDEN_PIXEL_TOLERANCE: f32 = 0.001;
+
+fn render_example(name: &str) -> skia::Image {
+    let mut v = Editor::new(Args {
+        filename: None,
+        headless_mode: HeadlessMode::None,
+        no_contour_ops: false,
+        render_options: None,
+    });
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(format!("{}.glif", name));
+    v.load_glif_impl(&path).unwrap();
+    v.rebuild_preview();
+
+    let mut i = RenderState::default();
+    i.viewport.winsize = (GOLDEN_SIZE.0 as f32, GOLDEN_SIZE.1 as f32);
+    i.viewport.factor = 0.4;
+    i.viewport.offset = (50., 1000.);
+
+    let mut surface = skia::surfaces::raster_n32_premul(GOLDEN_SIZE).unwrap();
+    render_frame(&mut v, &mut i, surface.canvas());
+    surface.image_snapshot()
+}
+
+fn pixels(image: &skia::Image) -> Vec<u8> {
+    let info = ImageInfo::new(
+        image.dimensions(),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    );
+    let row_bytes = info.min_row_bytes();
+    let mut ret = vec![0; row_bytes * image.height() as usize];
+    assert!(image.read_pixels(&info, &mut ret, row_bytes, (0, 0), skia::image::CachingHint::Allow));
+    ret
+}
+
+/// Checks one example against its golden image, or writes it when blessing.
+fn check_golden(name: &str) -> Result<(), String> {
+    let rendered = render_example(name);
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", name));
+
+    if env::var_os("MFEK_BLESS_GOLDEN").is_some() {
+        let png = rendered
+            .encode(None, EncodedImageFormat::PNG, None)
+            .expect("Failed to encode PNG");
+        fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        fs::write(&golden_path, png.as_bytes()).unwrap();
+        log::warn!("Wrote golden image {:?}", golden_path);
+        return Ok(());
+    }
+    if !golden_path.exists() {
+        return Err(format!("No golden image {:?}; run with MFEK_BLESS_GOLDEN=1 to write it", golden_path));
+    }
+
+    let golden = skia::Image::from_encoded(skia::Data::new_copy(&fs::read(&golden_path).unwrap()))
+        .ok_or_else(|| format!("{:?} is not an image", golden_path))?;
+    if golden.dimensions() != rendered.dimensions() {
+        return Err(format!("{} changed size", name));
+    }
+
+    let (golden, rendered) = (pixels(&golden), pixels(&rendered));
+    let differing = golden
+        .chunks(4)
+        .zip(rendered.chunks(4))
+        .filter(|(g, r)| {
+            g.iter()
+                .zip(r.iter())
+                .any(|(g, r)| g.abs_diff(*r) > GOLDEN_CHANNEL_TOLERANCE)
+        })
+        .count();
+    let allowed = (golden.len() / 4) as f32 * GOLDEN_PIXEL_TOLERANCE;
+    if differing as f32 > allowed {
+        return Err(format!(
+            "{} renders differently from {:?} ({} pixels differ)",
+            name, golden_path, differing
+        ));
+    }
+    Ok(())
+}
+
+/// Every .glif in examples/, so new examples are checked as soon as they're blessed.
+fn example_names() -> Vec<String> {
+    let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut ret: Vec<String> = fs::read_dir(examples)
+        .unwrap()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "glif" {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .collect();
+    ret.sort();
+    ret
+}
+
+#[test]
+fn golden_examples_test() {
+    let names = example_names();
+    assert!(!names.is_empty(), "No example glyphs to check");
+    let failures: Vec<String> = names.iter().filter_map(|name| check_golden(name).err()).collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,153 @@
+// Renders the glyph preview to a PNG or SVG file without a window or GPU, for --render. This is
+// used to make thumbnails on machines that can't run the editor itself.
+
+use glifrenderer::points;
+use glifrenderer::toggles::PreviewMode;
+use glifrenderer::viewport::Viewport;
+use skia_safe::{self as skia, Canvas, Color, EncodedImageFormat, Paint, PaintStyle, Rect};
+
+use super::glyph_path;
+use crate::editor::Editor;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderStyle {
+    Fill,
+    Outline,
+    Paper,
+}
+
+impl FromStr for RenderStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fill" => Ok(RenderStyle::Fill),
+            "outline" => Ok(RenderStyle::Outline),
+            "paper" => Ok(RenderStyle::Paper),
+            _ => Err(format!("Unknown render style {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub output: PathBuf,
+    /// Height of the output in pixels. The width follows from the glyph's advance.
+    pub size: u32,
+    /// Empty space around the glyph, in pixels.
+    pub padding: u32,
+    pub style: RenderStyle,
+    pub points: bool,
+}
+
+/// The area of the glyph we render, in glyph units: the advance width and the vertical metrics,
+/// grown to fit anything that sticks out of them.
+fn glyph_area(v: &Editor) -> Rect {
+    let metric = |name: &str| {
+        v.guidelines
+            .iter()
+            .find(|g| g.name.as_deref() == Some(name))
+            .map(|g| g.at.y)
+    };
+    let mut area = Rect::new(
+        0.,
+        metric("descender").unwrap_or(0.),
+        v.advance_width() as f32,
+        metric("ascender").unwrap_or(0.),
+    );
+    if let Some(bounds) = v.outline_bounds() {
+        area.join(bounds);
+    }
+    area
+}
+
+fn draw(v: &Editor, options: &RenderOptions, canvas: &Canvas, area: Rect, factor: f32) {
+    canvas.clear(Color::WHITE);
+
+    // Glyph space has y going up, images have it going down.
+    let padding = options.padding as f32;
+    canvas.translate((padding, padding));
+    canvas.scale((factor, -factor));
+    canvas.translate((-area.left, -area.bottom));
+
+    let preview = v.preview.as_ref().unwrap();
+    let mut viewport = Viewport::default();
+    viewport.factor = factor;
+
+    match options.style {
+        RenderStyle::Fill | RenderStyle::Outline => {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(Color::BLACK);
+            if options.style == RenderStyle::Outline {
+                paint.set_style(PaintStyle::Stroke);
+                paint.set_stroke_width(1. / factor);
+            }
+            canvas.draw_path(&glyph_path(preview), &paint);
+        }
+        RenderStyle::Paper => {
+            viewport.preview_mode = PreviewMode::Paper;
+            glifrenderer::glyph::draw(canvas, preview, &viewport, None);
+        }
+    }
+
+    if options.points {
+        v.with_glyph(|glif| {
+            points::draw_all(
+                glif,
+                &viewport,
+                v.get_active_layer(),
+                None,
+                None,
+                &HashSet::new(),
+                canvas,
+                false,
+            );
+        });
+    }
+}
+
+/// Renders the current glyph according to options. The format is chosen by the output's
+/// extension, either .png or .svg.
+pub fn render_to_file(v: &Editor, options: &RenderOptions) -> Result<(), String> {
+    let area = glyph_area(v);
+    if area.height() <= 0. || area.width() <= 0. {
+        return Err("Glyph is empty and has no metrics, nothing to render.".to_string());
+    }
+
+    let inner_height = options.size.saturating_sub(options.padding * 2).max(1) as f32;
+    let factor = inner_height / area.height();
+    let width = (area.width() * factor).ceil() as i32 + options.padding as i32 * 2;
+    let height = options.size as i32;
+
+    let ext = options
+        .output
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+    let data = match ext.as_deref() {
+        Some("png") => {
+            let mut surface = skia::surfaces::raster_n32_premul((width, height))
+                .ok_or_else(|| "Failed to create raster surface".to_string())?;
+            draw(v, options, surface.canvas(), area, factor);
+            surface
+                .image_snapshot()
+                .encode(None, EncodedImageFormat::PNG, None)
+                .ok_or_else(|| "Failed to encode PNG".to_string())?
+        }
+        Some("svg") => {
+            let bounds = Rect::from_wh(width as f32, height as f32);
+            let canvas = skia::svg::Canvas::new(bounds, None);
+            draw(v, options, &canvas, area, factor);
+            canvas.end()
+        }
+        _ => return Err(format!("Can only render to .png or .svg, not {:?}", options.output)),
+    };
+
+    fs::write(&options.output, data.as_bytes())
+        .map_err(|e| format!("Failed to write {:?}: {}", options.output, e))
+}
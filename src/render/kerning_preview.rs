@@ -0,0 +1,354 @@
+// The kerning preview is a line of text drawn below the glyph being edited, made of the other
+// glyphs in the UFO, so shapes and spacing can be judged in context. The text can use characters,
+// /glyphname (ended by a space or another slash), and /? for the glyph being edited, which is drawn
+// from the live preview so it updates as you edit.
+
+use glifparser::glif::mfek::MFEKGlif;
+use glifparser::{FlattenedGlif as _, MFEKPointData};
+use skia_safe::{Canvas, Paint, PaintStyle, Path};
+
+use crate::constants::{
+    KERNING_PREVIEW_CURRENT_FILL, KERNING_PREVIEW_DEFAULT_TEXT, KERNING_PREVIEW_FILL,
+    KERNING_PREVIEW_GAP,
+};
+use crate::editor::siblings::{glyph_unicodes, unicodes_of};
+use crate::editor::Editor;
+use super::glyph_path;
+
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PreviewGlyph {
+    Current,
+    Named(String),
+}
+
+/// Everything we've read from the UFO. Dropped whenever its kerning, groups or contents change
+/// on disk; when one of its glyphs does, only what was read from that glyph is.
+#[derive(Default)]
+struct FontData {
+    font: PathBuf,
+    // file name → glyph name, from contents.plist
+    files: HashMap<String, String>,
+    // Reading every glyph for this takes a while on big fonts, so it's done on another thread and
+    // is empty until that's finished.
+    cmap: HashMap<char, String>,
+    cmap_rx: Option<Receiver<HashMap<char, String>>>,
+    // None if the glyph couldn't be read, so we don't try again every frame
+    glyphs: HashMap<String, Option<(Path, f32)>>,
+    kerning: HashMap<String, HashMap<String, f32>>,
+    // glyph name → public.kern1/public.kern2 group name
+    kern1: HashMap<String, String>,
+    kern2: HashMap<String, String>,
+}
+
+pub struct KerningPreview {
+    pub enabled: bool,
+    pub text: String,
+    data: Option<FontData>,
+}
+
+impl Default for KerningPreview {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            text: KERNING_PREVIEW_DEFAULT_TEXT.to_string(),
+            data: None,
+        }
+    }
+}
+
+fn parse_text(text: &str, cmap: &HashMap<char, String>, current: &[char]) -> Vec<PreviewGlyph> {
+    let mut ret = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '/' {
+            if current.contains(&c) {
+                ret.push(PreviewGlyph::Current);
+            } else if let Some(name) = cmap.get(&c) {
+                ret.push(PreviewGlyph::Named(name.clone()));
+            }
+            continue;
+        }
+
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '/' {
+                break;
+            }
+            chars.next();
+            if c == ' ' {
+                break;
+            }
+            name.push(c);
+        }
+
+        match name.as_str() {
+            "?" => ret.push(PreviewGlyph::Current),
+            "" => {}
+            _ => ret.push(PreviewGlyph::Named(name)),
+        }
+    }
+    ret
+}
+
+fn read_plist_dict(path: PathBuf) -> plist::Dictionary {
+    if !path.exists() {
+        return plist::Dictionary::new();
+    }
+    match plist::Value::from_file(&path).map(|v| v.into_dictionary()) {
+        Ok(Some(dict)) => dict,
+        _ => {
+            log::error!("Failed to read {:?} for the kerning preview", path);
+            plist::Dictionary::new()
+        }
+    }
+}
+
+fn plist_number(value: &plist::Value) -> Option<f32> {
+    value
+        .as_real()
+        .map(|r| r as f32)
+        .or_else(|| value.as_signed_integer().map(|i| i as f32))
+}
+
+impl FontData {
+    fn load(v: &Editor, font: PathBuf) -> Self {
+        let mut ret = FontData {
+            font: font.clone(),
+            ..Default::default()
+        };
+
+        match v.sibling_contents() {
+            Ok(contents) => {
+                ret.files = contents
+                    .iter()
+                    .filter_map(|(name, file)| Some((file.as_string()?.to_string(), name.clone())))
+                    .collect();
+                let glyphs_dir = v.glyphs_dir().unwrap_or_default();
+                let current = v.with_glyph(|glyph| glyph.name.clone());
+                let (tx, rx) = mpsc::channel();
+                thread::spawn(move || {
+                    // If the preview's been invalidated meanwhile, nobody's listening, and that's fine.
+                    let _ = tx.send(glyph_unicodes(&glyphs_dir, &contents, &current));
+                });
+                ret.cmap_rx = Some(rx);
+            }
+            Err(e) => log::error!("Kerning preview can't list glyphs: {}", e),
+        }
+
+        for (first, seconds) in read_plist_dict(font.join("kerning.plist")) {
+            let seconds = seconds
+                .as_dictionary()
+                .map(|d| {
+                    d.iter()
+                        .filter_map(|(second, value)| Some((second.clone(), plist_number(value)?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            ret.kerning.insert(first, seconds);
+        }
+
+        for (group, members) in read_plist_dict(font.join("groups.plist")) {
+            let map = if group.starts_with("public.kern1.") {
+                &mut ret.kern1
+            } else if group.starts_with("public.kern2.") {
+                &mut ret.kern2
+            } else {
+                continue;
+            };
+            for member in members.as_array().into_iter().flatten() {
+                if let Some(member) = member.as_string() {
+                    map.insert(member.to_string(), group.clone());
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn poll_cmap(&mut self) {
+        if let Some(rx) = self.cmap_rx.as_ref() {
+            match rx.try_recv() {
+                Ok(cmap) => {
+                    self.cmap = cmap;
+                    self.cmap_rx = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.cmap_rx = None,
+            }
+        }
+    }
+
+    /// Forgets what was read from one glyph, and rereads its codepoints.
+    fn invalidate_glyph(&mut self, path: &FsPath) {
+        let name = match path
+            .file_name()
+            .and_then(|f| self.files.get(f.to_string_lossy().as_ref()))
+        {
+            Some(name) => name.clone(),
+            None => return,
+        };
+        self.glyphs.remove(&name);
+        // If the cmap is still being built, it'll read the glyph as it is now anyway.
+        if self.cmap_rx.is_none() {
+            self.cmap.retain(|_, n| *n != name);
+            match unicodes_of(path) {
+                Ok(unicodes) => {
+                    for c in unicodes {
+                        self.cmap.insert(c, name.clone());
+                    }
+                }
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+    }
+
+    fn glyph(&mut self, v: &Editor, name: &str) -> Option<&(Path, f32)> {
+        if !self.glyphs.contains_key(name) {
+            let glyph = v
+                .sibling_path(name)
+                .and_then(|path| {
+                    let glif: glifparser::Glif<MFEKPointData> =
+                        glifparser::read_from_filename(&path).map_err(|e| format!("{:?}", e))?;
+                    let mut glif: MFEKGlif<MFEKPointData> = glif.into();
+                    glif.filename = Some(path);
+                    // Components need the glyph to be flattened, but we can still draw the outline
+                    // if that fails.
+                    if let Ok(flattened) = glif.flattened(&mut None) {
+                        glif.flattened = flattened.flattened;
+                    }
+                    Ok((glyph_path(&glif), glif.width.unwrap_or(0) as f32))
+                })
+                .map_err(|e| log::warn!("Kerning preview can't draw {}: {}", name, e))
+                .ok();
+            self.glyphs.insert(name.to_string(), glyph);
+        }
+        self.glyphs[name].as_ref()
+    }
+
+    /// Looks up a kerning pair the way the UFO spec says to: glyph–glyph, glyph–group,
+    /// group–glyph, then group–group.
+    fn kerning(&self, left: &str, right: &str) -> f32 {
+        let left_group = self.kern1.get(left).map(|g| g.as_str());
+        let right_group = self.kern2.get(right).map(|g| g.as_str());
+        [
+            (Some(left), Some(right)),
+            (Some(left), right_group),
+            (left_group, Some(right)),
+            (left_group, right_group),
+        ]
+        .into_iter()
+        .find_map(|pair| match pair {
+            (Some(l), Some(r)) => self.kerning.get(l)?.get(r).copied(),
+            _ => None,
+        })
+        .unwrap_or(0.)
+    }
+}
+
+impl KerningPreview {
+    /// Forgets everything read from the UFO, so it's read again next time we draw.
+    pub fn invalidate(&mut self) {
+        self.data = None;
+    }
+
+    /// Forgets what was read from a glyph of the UFO that changed.
+    pub fn invalidate_glyph(&mut self, path: &FsPath) {
+        if let Some(data) = self.data.as_mut() {
+            data.invalidate_glyph(path);
+        }
+    }
+
+    pub fn draw(&mut self, v: &Editor, canvas: &Canvas) {
+        if !self.enabled {
+            return;
+        }
+        let font = match v.font_dir() {
+            Some(font) => font,
+            None => return,
+        };
+        if self.data.as_ref().map(|d| d.font != font).unwrap_or(true) {
+            self.data = Some(FontData::load(v, font));
+        }
+        let data = self.data.as_mut().unwrap();
+        data.poll_cmap();
+
+        let (current_name, current_unicode, current_width) = v.with_glyph(|glyph| {
+            (
+                glyph.name.clone(),
+                glyph.unicode.clone(),
+                glyph.width.unwrap_or(0) as f32,
+            )
+        });
+        let current_path = v
+            .preview
+            .as_ref()
+            .map(glyph_path)
+            .unwrap_or_else(Path::new);
+
+        let glyphs = parse_text(&self.text, &data.cmap, &current_unicode);
+
+        let metric = |name: &str, default: f32| {
+            v.guidelines
+                .iter()
+                .find(|g| g.name.as_deref() == Some(name))
+                .map(|g| g.at.y)
+                .unwrap_or(default)
+        };
+        let baseline = metric("descender", -250.) - metric("ascender", 750.) - KERNING_PREVIEW_GAP;
+
+        // Lay out the line first, so that we can put the first copy of the current glyph right
+        // under the glyph being edited.
+        let mut placed = vec![];
+        let mut x = 0.;
+        let mut previous: Option<String> = None;
+        for glyph in glyphs {
+            let glyph = match glyph {
+                PreviewGlyph::Named(name) if name == current_name => PreviewGlyph::Current,
+                glyph => glyph,
+            };
+            let name = match &glyph {
+                PreviewGlyph::Current => current_name.clone(),
+                PreviewGlyph::Named(name) => name.clone(),
+            };
+            if let Some(previous) = previous.as_ref() {
+                x += data.kerning(previous, &name);
+            }
+            let (path, width) = match &glyph {
+                PreviewGlyph::Current => (current_path.clone(), current_width),
+                PreviewGlyph::Named(name) => match data.glyph(v, name) {
+                    Some((path, width)) => (path.clone(), *width),
+                    None => continue,
+                },
+            };
+            placed.push((x, path, glyph == PreviewGlyph::Current));
+            x += width;
+            previous = Some(name);
+        }
+
+        let offset = placed
+            .iter()
+            .find(|(_, _, current)| *current)
+            .map(|(x, _, _)| *x)
+            .unwrap_or(0.);
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+        for (x, path, current) in placed {
+            paint.set_color(if current {
+                KERNING_PREVIEW_CURRENT_FILL
+            } else {
+                KERNING_PREVIEW_FILL
+            });
+            canvas.save();
+            canvas.translate((x - offset, baseline));
+            canvas.draw_path(&path, &paint);
+            canvas.restore();
+        }
+    }
+}
@@ -3,9 +3,9 @@ use skia_safe::{Canvas, Font, Matrix, Paint, Path, Point, Rect};
 use MFEKmath::{vec2, Vector};
 
 use crate::editor::Editor;
+use crate::settings;
 use crate::tools::cut::{Cut, Intersection};
-use crate::user_interface::Interface;
-use glifrenderer::constants::MEASURE_STROKE;
+use crate::user_interface::RenderState;
 
 pub struct Measure {
     pub start_point: Option<(f32, f32)>,
@@ -14,7 +14,7 @@ pub struct Measure {
 }
 
 impl Measure {
-    pub fn draw_line(&self, i: &Interface, v: &Editor, canvas: &Canvas, factor: f32) {
+    pub fn draw_line(&self, i: &RenderState, v: &Editor, canvas: &Canvas, factor: f32) {
         if !self.enabled {
             return;
         }
@@ -155,7 +155,7 @@ impl Measure {
         factor: f32,
     ) {
         let mut paint = Paint::default();
-        paint.set_color(MEASURE_STROKE);
+        paint.set_color(settings::current().colors.measure);
         paint.set_anti_alias(true);
 
         let mut font = Font::default();
@@ -2,67 +2,118 @@
 
 use glifparser::matrix::ToSkiaMatrix;
 use glifrenderer::anchors::draw_anchors;
-use glifrenderer::constants::*;
 use glifrenderer::glyph::draw_components;
 use glifrenderer::grid;
 use glifrenderer::guidelines;
 use glifrenderer::points;
 use glifrenderer::toggles::*;
 
-use skia_safe::{self as skia, Canvas};
+use glifparser::glif::mfek::MFEKGlif;
+use glifparser::outline::skia::ToSkiaPaths as _;
+use glifparser::MFEKPointData;
+use skia_safe::{self as skia, Canvas, Paint, Path};
+
+use crate::editor::Editor;
+use crate::settings;
+use crate::user_interface::MouseInfo;
+use glifrenderer::viewport::Viewport;
+
+use self::curvature_comb::CurvatureComb;
+use self::kerning_preview::KerningPreview;
+use self::measure::Measure;
+
+#[cfg(test)]
+mod golden;
+pub mod curvature_comb;
+pub mod headless;
+pub mod kerning_preview;
+pub mod measure;
 
-use crate::user_interface::PAPER_DRAW_GUIDELINES;
-use crate::{editor::Editor, user_interface::Interface};
+/// The part of the interface the renderer and the tools' draw hooks need. It doesn't depend on SDL
+/// or OpenGL, so it can be used to draw into any Canvas.
+pub struct RenderState {
+    pub grid: grid::Grid,
+    pub measure: Measure,
+    pub kerning_preview: KerningPreview,
+    pub curvature_comb: CurvatureComb,
+    pub mouse_info: MouseInfo,
+    pub viewport: Viewport,
+}
 
-pub mod measure;
-mod speed_visualization;
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            grid: grid::Grid::default(),
+            measure: Measure {
+                start_point: None,
+                end_point: None,
+                enabled: true,
+            },
+            kerning_preview: KerningPreview::default(),
+            curvature_comb: CurvatureComb::default(),
+            mouse_info: MouseInfo::default(),
+            viewport: Viewport::default(),
+        }
+    }
+}
 
-pub fn render_frame(v: &mut Editor, i: &mut Interface, canvas: &Canvas) {
+/// Combines the visible layers and flattened components of a glyph into one path, for when a
+/// glyph needs to be drawn as a whole rather than edited.
+pub fn glyph_path(glif: &MFEKGlif<MFEKPointData>) -> Path {
+    let mut path = Path::new();
+    for layer in glif.layers.iter().filter(|l| l.visible) {
+        path.add_path(&layer.outline.to_skia_paths(None).combined(), (0., 0.), None);
+    }
+    if let Some(flattened) = glif.flattened.as_ref() {
+        path.add_path(&flattened.to_skia_paths(None).combined(), (0., 0.), None);
+    }
+    path
+}
+
+pub fn render_frame(v: &mut Editor, i: &mut RenderState, canvas: &Canvas) {
     canvas.save();
 
     let pm = i.viewport.preview_mode;
-    canvas.clear(if pm == PreviewMode::Paper {
-        PAPER_BGCOLOR
+    let settings = settings::current();
+    canvas.clear(skia::Color::new(if pm == PreviewMode::Paper {
+        settings.colors.paper_background
     } else {
-        BACKGROUND_COLOR
-    });
+        settings.colors.background
+    }));
 
     // This will change the SkCanvas transformation matrix, and everything from here to
     // canvas.restore() will need to take that matrix into consideration.
     i.viewport.redraw(canvas);
 
-    let dropped = v.with_glyph(|glif| {
-        let mut dropped = vec![];
-        for layer in &glif.layers {
-            for (l_image, i_matrix) in &layer.images {
-                if !v.images.contains_key(&l_image.filename) {
-                    log::error!("Layer's image {} has gone out of scope and will be dropped! Did you save the glyph into a location without the image?", l_image.filename.to_string_lossy());
-                    dropped.push(l_image.filename.clone());
+    v.with_glyph(|glif| {
+        for (li, layer) in glif.layers.iter().enumerate() {
+            for (ii, (l_image, i_matrix)) in layer.images.iter().enumerate() {
+                // Images that couldn't be read are kept in the glyph, so they can be relinked
+                // from the image tool; there's just nothing to draw.
+                let image = match v.images.get(&l_image.filename) {
+                    Some(image) => image,
+                    None => continue,
+                };
+                let settings = v.image_settings(li, ii);
+                if !settings.visible {
                     continue;
                 }
-                let image = &v.images[&l_image.filename];
                 let tm = canvas.local_to_device_as_3x3();
                 canvas.save();
                 let matrix2 = skia::EncodedOrigin::to_matrix(skia::EncodedOrigin::BottomLeft, (image.img.width(), image.img.height()));
                 let matrix = tm * i_matrix.to_skia_matrix() * matrix2;
                 canvas.set_matrix(&((matrix).into()));
                 //eprintln!("{:?}", Matrix::new_identity().set_rotate(45., None).to_affine());
-                canvas.draw_image(&image.img, (0., 0.), None);
+                canvas.clip_rect(settings.crop_rect((image.img.width(), image.img.height())), None, true);
+                let mut paint = Paint::default();
+                paint.set_alpha_f(settings.opacity);
+                canvas.draw_image(&image.img, (0., 0.), Some(&paint));
                 canvas.restore();
             }
         }
-        dropped
     });
 
-    for dropee in dropped {
-        v.with_glyph_mut_no_history(|glif| {
-            for layer in glif.layers.iter_mut() {
-                layer.images.retain(|(gi, _)| gi.filename != dropee);
-            }
-        });
-    }
-
-    if pm != PreviewMode::Paper || PAPER_DRAW_GUIDELINES {
+    if pm != PreviewMode::Paper || settings.paper_draw_guidelines {
         guidelines::draw_baseline::<()>(&i.viewport, canvas);
         let local_guidelines = v.with_glyph(|glyph| {
             glyph
@@ -78,9 +129,9 @@ pub fn render_frame(v: &mut Editor, i: &mut Interface, canvas: &Canvas) {
                 canvas,
                 &guideline,
                 if data.right {
-                    Some(RBEARING_STROKE)
+                    Some(settings.colors.right_bearing.into())
                 } else if data.format {
-                    Some(UFO_GUIDELINE_STROKE)
+                    Some(settings.colors.ufo_guideline.into())
                 } else {
                     None
                 },
@@ -92,8 +143,9 @@ pub fn render_frame(v: &mut Editor, i: &mut Interface, canvas: &Canvas) {
     }
 
     glifrenderer::glyph::draw(canvas, v.preview.as_ref().unwrap(), &i.viewport, None);
+    i.kerning_preview.draw(v, canvas);
 
-    speed_visualization::draw_velocity(v, i, canvas);
+    i.curvature_comb.draw(v, i, canvas);
     v.with_glyph(|glyph| {
         // Cache component rects and flattened outline on MFEKGlif
         draw_components(glyph, &i.viewport, canvas);
@@ -1,7 +1,29 @@
+// Preferences kept across sessions in settings.json in the config directory. Grid and curvature
+// comb settings are what those start as each session; the rest are read wherever they're used, so
+// changes to them show at once. Any setting missing from the file is left at its default, so old
+// files keep working as settings are added.
+//
+// Only colours MFEKglif draws with itself can be set; points and outlines are drawn by
+// glifrenderer, whose colours are fixed.
+
 use directories::BaseDirs;
+use glifrenderer::constants::{
+    BACKGROUND_COLOR, MEASURE_STROKE, PAPER_BGCOLOR, RBEARING_STROKE, UFO_GUIDELINE_STROKE,
+};
 use lazy_static::lazy_static;
 use log;
+use serde_json::{json, Value};
+use skia_safe::Color;
+
+use crate::constants::{
+    BIG_OFFSET_FACTOR, COMB_DISCONTINUITY_STROKE, COMB_INFLECTION_FILL, COMB_OUTLINE_STROKE,
+    COMB_TEETH_STROKE, OFFSET_FACTOR, SIDEBEARING_HANDLE_FILL, SMART_GUIDE_STROKE,
+    TINY_OFFSET_FACTOR,
+};
+use crate::render::curvature_comb::CurvatureComb;
+use crate::render::RenderState;
 
+use std::cell::RefCell;
 use std::{fs, path::PathBuf};
 
 lazy_static! {
@@ -16,3 +38,299 @@ lazy_static! {
         ret
     };
 }
+
+lazy_static! {
+    pub static ref CONFIG_SETTINGS_PATH: PathBuf = {
+        let mut pb = CONFIG_PATH.clone();
+
+        pb.push("settings");
+        pb.set_extension("json");
+        pb
+    };
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridSettings {
+    pub show: bool,
+    pub spacing: f32,
+    pub offset: f32,
+    pub slope: Option<f32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CombSettings {
+    pub enabled: bool,
+    pub all_contours: bool,
+    pub density: usize,
+    pub scale: f32,
+    pub mark_discontinuities: bool,
+}
+
+/// How far, in font units, the nudge commands move points or the view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NudgeSettings {
+    pub normal: f32,
+    pub big: f32,
+    pub tiny: f32,
+}
+
+/// Colours as ARGB, like the constants they default to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorSettings {
+    pub background: u32,
+    pub paper_background: u32,
+    pub right_bearing: u32,
+    pub ufo_guideline: u32,
+    pub measure: u32,
+    pub smart_guide: u32,
+    pub sidebearing_handle: u32,
+    pub comb_teeth: u32,
+    pub comb_outline: u32,
+    pub comb_discontinuity: u32,
+    pub comb_inflection: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub grid: GridSettings,
+    pub curvature_comb: CombSettings,
+    pub nudge: NudgeSettings,
+    /// Draw guidelines in paper mode too.
+    pub paper_draw_guidelines: bool,
+    pub colors: ColorSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let grid = glifrenderer::grid::Grid::default();
+        let comb = CurvatureComb::default();
+        Self {
+            grid: GridSettings {
+                show: grid.show,
+                spacing: grid.spacing,
+                offset: grid.offset,
+                slope: grid.slope,
+            },
+            curvature_comb: CombSettings {
+                enabled: comb.enabled,
+                all_contours: comb.all_contours,
+                density: comb.density,
+                scale: comb.scale,
+                mark_discontinuities: comb.mark_discontinuities,
+            },
+            nudge: NudgeSettings {
+                normal: OFFSET_FACTOR,
+                big: BIG_OFFSET_FACTOR,
+                tiny: TINY_OFFSET_FACTOR,
+            },
+            paper_draw_guidelines: false,
+            colors: ColorSettings {
+                background: argb(BACKGROUND_COLOR),
+                paper_background: argb(PAPER_BGCOLOR),
+                right_bearing: argb(RBEARING_STROKE),
+                ufo_guideline: argb(UFO_GUIDELINE_STROKE),
+                measure: argb(MEASURE_STROKE),
+                smart_guide: SMART_GUIDE_STROKE,
+                sidebearing_handle: SIDEBEARING_HANDLE_FILL,
+                comb_teeth: COMB_TEETH_STROKE,
+                comb_outline: COMB_OUTLINE_STROKE,
+                comb_discontinuity: COMB_DISCONTINUITY_STROKE,
+                comb_inflection: COMB_INFLECTION_FILL,
+            },
+        }
+    }
+}
+
+fn argb(color: impl Into<Color>) -> u32 {
+    let color: Color = color.into();
+    (color.a() as u32) << 24 | (color.r() as u32) << 16 | (color.g() as u32) << 8 | color.b() as u32
+}
+
+/// Colours are written like CSS's, #rrggbbaa.
+fn color_to_json(argb: u32) -> Value {
+    Value::String(format!("#{:06x}{:02x}", argb & 0xff_ffff, argb >> 24))
+}
+
+fn color_from_json(v: &Value) -> Option<u32> {
+    let hex = v.as_str()?.strip_prefix('#')?;
+    let rgba = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(0xff_000000 | rgba),
+        8 => Some((rgba & 0xff) << 24 | rgba >> 8),
+        _ => None,
+    }
+}
+
+impl Settings {
+    /// The grid and curvature comb are set up as these settings say.
+    pub fn apply(&self, state: &mut RenderState) {
+        state.grid.show = self.grid.show;
+        state.grid.spacing = self.grid.spacing;
+        state.grid.offset = self.grid.offset;
+        state.grid.slope = self.grid.slope;
+        state.curvature_comb.enabled = self.curvature_comb.enabled;
+        state.curvature_comb.all_contours = self.curvature_comb.all_contours;
+        state.curvature_comb.density = self.curvature_comb.density;
+        state.curvature_comb.scale = self.curvature_comb.scale;
+        state.curvature_comb.mark_discontinuities = self.curvature_comb.mark_discontinuities;
+    }
+
+    /// Takes the grid and curvature comb settings from how they're set up now, since their own
+    /// windows change them too.
+    pub fn capture(&mut self, state: &RenderState) {
+        self.grid = GridSettings {
+            show: state.grid.show,
+            spacing: state.grid.spacing,
+            offset: state.grid.offset,
+            slope: state.grid.slope,
+        };
+        self.curvature_comb = CombSettings {
+            enabled: state.curvature_comb.enabled,
+            all_contours: state.curvature_comb.all_contours,
+            density: state.curvature_comb.density,
+            scale: state.curvature_comb.scale,
+            mark_discontinuities: state.curvature_comb.mark_discontinuities,
+        };
+    }
+
+    pub fn to_json(&self) -> Value {
+        let c = &self.colors;
+        json!({
+            "grid": {
+                "show": self.grid.show,
+                "spacing": self.grid.spacing,
+                "offset": self.grid.offset,
+                "slope": self.grid.slope,
+            },
+            "curvatureComb": {
+                "enabled": self.curvature_comb.enabled,
+                "allContours": self.curvature_comb.all_contours,
+                "density": self.curvature_comb.density,
+                "scale": self.curvature_comb.scale,
+                "markDiscontinuities": self.curvature_comb.mark_discontinuities,
+            },
+            "nudge": {
+                "normal": self.nudge.normal,
+                "big": self.nudge.big,
+                "tiny": self.nudge.tiny,
+            },
+            "paperDrawGuidelines": self.paper_draw_guidelines,
+            "colors": {
+                "background": color_to_json(c.background),
+                "paperBackground": color_to_json(c.paper_background),
+                "rightBearing": color_to_json(c.right_bearing),
+                "ufoGuideline": color_to_json(c.ufo_guideline),
+                "measure": color_to_json(c.measure),
+                "smartGuide": color_to_json(c.smart_guide),
+                "sidebearingHandle": color_to_json(c.sidebearing_handle),
+                "combTeeth": color_to_json(c.comb_teeth),
+                "combOutline": color_to_json(c.comb_outline),
+                "combDiscontinuity": color_to_json(c.comb_discontinuity),
+                "combInflection": color_to_json(c.comb_inflection),
+            },
+        })
+    }
+
+    pub fn from_json(v: &Value) -> Self {
+        let mut s = Self::default();
+        let real = |v: &Value, into: &mut f32| {
+            if let Some(f) = v.as_f64() {
+                *into = f as f32;
+            }
+        };
+        let boolean = |v: &Value, into: &mut bool| {
+            if let Some(b) = v.as_bool() {
+                *into = b;
+            }
+        };
+        let color = |v: &Value, into: &mut u32| {
+            if let Some(c) = color_from_json(v) {
+                *into = c;
+            }
+        };
+
+        let grid = &v["grid"];
+        boolean(&grid["show"], &mut s.grid.show);
+        real(&grid["spacing"], &mut s.grid.spacing);
+        real(&grid["offset"], &mut s.grid.offset);
+        // null turns the italic grid off, where leaving it out leaves it as it was.
+        if let Some(slope) = grid.get("slope") {
+            s.grid.slope = slope.as_f64().map(|f| f as f32);
+        }
+
+        let comb = &v["curvatureComb"];
+        boolean(&comb["enabled"], &mut s.curvature_comb.enabled);
+        boolean(&comb["allContours"], &mut s.curvature_comb.all_contours);
+        if let Some(density) = comb["density"].as_u64() {
+            s.curvature_comb.density = density as usize;
+        }
+        real(&comb["scale"], &mut s.curvature_comb.scale);
+        boolean(&comb["markDiscontinuities"], &mut s.curvature_comb.mark_discontinuities);
+
+        let nudge = &v["nudge"];
+        real(&nudge["normal"], &mut s.nudge.normal);
+        real(&nudge["big"], &mut s.nudge.big);
+        real(&nudge["tiny"], &mut s.nudge.tiny);
+
+        boolean(&v["paperDrawGuidelines"], &mut s.paper_draw_guidelines);
+
+        let colors = &v["colors"];
+        let c = &mut s.colors;
+        color(&colors["background"], &mut c.background);
+        color(&colors["paperBackground"], &mut c.paper_background);
+        color(&colors["rightBearing"], &mut c.right_bearing);
+        color(&colors["ufoGuideline"], &mut c.ufo_guideline);
+        color(&colors["measure"], &mut c.measure);
+        color(&colors["smartGuide"], &mut c.smart_guide);
+        color(&colors["sidebearingHandle"], &mut c.sidebearing_handle);
+        color(&colors["combTeeth"], &mut c.comb_teeth);
+        color(&colors["combOutline"], &mut c.comb_outline);
+        color(&colors["combDiscontinuity"], &mut c.comb_discontinuity);
+        color(&colors["combInflection"], &mut c.comb_inflection);
+        s
+    }
+}
+
+/// The saved settings, or the defaults if none have been saved.
+pub fn load_settings() -> Result<Settings, String> {
+    if !CONFIG_SETTINGS_PATH.exists() {
+        return Ok(Settings::default());
+    }
+    let text = fs::read_to_string(&*CONFIG_SETTINGS_PATH)
+        .map_err(|e| format!("Couldn't read `{}`: {}", CONFIG_SETTINGS_PATH.display(), e))?;
+    let v: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("`{}` isn't valid JSON: {}", CONFIG_SETTINGS_PATH.display(), e))?;
+    Ok(Settings::from_json(&v))
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    fs::write(&*CONFIG_SETTINGS_PATH, serde_json::to_string_pretty(&settings.to_json()).unwrap())
+        .map_err(|e| format!("Couldn't save settings to `{}`: {}", CONFIG_SETTINGS_PATH.display(), e))
+}
+
+thread_local! {
+    static SETTINGS: RefCell<Settings> = RefCell::new(Settings::default());
+}
+
+/// The settings in use.
+pub fn current() -> Settings {
+    SETTINGS.with(|s| *s.borrow())
+}
+
+pub fn set_current(settings: Settings) {
+    SETTINGS.with(|s| *s.borrow_mut() = settings);
+}
+
+#[test]
+fn settings_json_test() {
+    let mut settings = Settings::default();
+    settings.grid.slope = Some(0.25);
+    settings.nudge.big = 50.;
+    settings.colors.measure = 0x80_123456;
+    assert_eq!(Settings::from_json(&settings.to_json()), settings);
+    assert_eq!(color_to_json(0x80_123456), Value::String("#12345680".to_string()));
+    // Settings that aren't in the file are left as they were.
+    let partial = Settings::from_json(&json!({ "nudge": { "tiny": 2 } }));
+    assert_eq!(partial.nudge.tiny, 2.);
+    assert_eq!(partial.grid, Settings::default().grid);
+}
@@ -119,7 +119,7 @@ impl ToolBehavior for MovePoint {
     }
 
     // We draw a preview to show if the point will be merged with another when you let go of the mouse button.
-    fn draw(&mut self, v: &Editor, i: &Interface, canvas: &Canvas) {
+    fn draw(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
         // This draws a preview to show if we're overlapping a point we can merge with or not.
         // Note that all tool draw events draw over the glyph view.
         if v.contour_idx.is_none() || v.point_idx.is_none() {
@@ -128,6 +128,11 @@ impl ToolBehavior for MovePoint {
 
         let (vci, vpi) = (v.contour_idx.unwrap(), v.point_idx.unwrap());
 
+        // Smart guides show what the dragged point is lined up with.
+        if v.is_modifying() {
+            editor::smart_guides::draw_smart_guides(v, i, canvas);
+        }
+
         // are we overlapping a point?
         if let Some((ci, pi, WhichHandle::Neither)) =
             clicked_point_or_handle(v, i, i.mouse_info.raw_position, Some((vci, vpi)))
@@ -0,0 +1,74 @@
+use crate::editor::metrics::Sidebearing;
+
+use super::prelude::*;
+
+// Dragging the right sidebearing changes the advance width. Dragging the left one moves the glyph
+// instead, so the origin stays at zero, and changes the advance width to keep the right sidebearing.
+#[derive(Clone, Debug)]
+pub struct MoveSidebearing {
+    side: Sidebearing,
+    mouse_info: MouseInfo,
+    // how far we've moved, in whole units, since the drag started
+    moved: f32,
+}
+
+impl MoveSidebearing {
+    pub fn new(side: Sidebearing, mouse_info: MouseInfo) -> Self {
+        Self {
+            side,
+            mouse_info,
+            moved: 0.,
+        }
+    }
+
+    pub fn mouse_moved(&mut self, v: &mut Editor, _i: &mut Interface, mouse_info: MouseInfo) {
+        let delta = (mouse_info.position.0 - self.mouse_info.position.0).round() - self.moved;
+        if delta == 0. {
+            return;
+        }
+
+        if !v.is_modifying() {
+            v.begin_modification("Move sidebearing.", false);
+        }
+
+        if self.side == Sidebearing::Left {
+            editor::util::move_all_layers(v, delta, f32::NAN);
+        }
+        v.with_glyph_mut(|glyph| {
+            let width = glyph.width.unwrap_or(0) as f32;
+            let width = match self.side {
+                Sidebearing::Left => width - delta,
+                Sidebearing::Right => width + delta,
+            };
+            glyph.width = Some(width.max(0.) as u64);
+        });
+        v.add_width_guidelines();
+
+        self.moved += delta;
+    }
+
+    pub fn mouse_released(&mut self, v: &mut Editor, _i: &mut Interface, mouse_info: MouseInfo) {
+        if mouse_info.button == self.mouse_info.button {
+            if v.is_modifying() {
+                v.end_modification();
+            }
+            v.pop_behavior();
+        }
+    }
+}
+
+impl ToolBehavior for MoveSidebearing {
+    fn event(&mut self, v: &mut Editor, i: &mut Interface, event: EditorEvent) {
+        if let EditorEvent::MouseEvent {
+            event_type,
+            mouse_info,
+        } = event
+        {
+            match event_type {
+                MouseEventType::Released => self.mouse_released(v, i, mouse_info),
+                MouseEventType::Moved => self.mouse_moved(v, i, mouse_info),
+                _ => {}
+            }
+        }
+    }
+}
@@ -39,11 +39,11 @@ impl PanBehavior {
 
 impl PanBehavior {
     pub fn nudge_factor(command: Command) -> f32 {
-        use crate::constants::{BIG_OFFSET_FACTOR, OFFSET_FACTOR, TINY_OFFSET_FACTOR};
+        let nudge = crate::settings::current().nudge;
         match command {
-            NudgeTinyUp | NudgeTinyDown | NudgeTinyLeft | NudgeTinyRight => TINY_OFFSET_FACTOR,
-            NudgeBigUp | NudgeBigDown | NudgeBigLeft | NudgeBigRight => BIG_OFFSET_FACTOR,
-            NudgeUp | NudgeDown | NudgeLeft | NudgeRight => OFFSET_FACTOR,
+            NudgeTinyUp | NudgeTinyDown | NudgeTinyLeft | NudgeTinyRight => nudge.tiny,
+            NudgeBigUp | NudgeBigDown | NudgeBigLeft | NudgeBigRight => nudge.big,
+            NudgeUp | NudgeDown | NudgeLeft | NudgeRight => nudge.normal,
             _ => f32::NAN,
         }
     }
@@ -26,5 +26,5 @@ pub use glifparser::{glif::MFEKGlif, Handle, WhichHandle};
 
 //User interface
 pub use crate::command::CommandMod;
-pub use crate::user_interface::{Interface, MouseInfo};
+pub use crate::user_interface::{Interface, MouseInfo, RenderState};
 pub use skia_safe::{Paint, PaintStyle, Path, Rect};
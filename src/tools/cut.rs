@@ -12,7 +12,7 @@ use glifparser::glif::contour_operations::ContourOperation;
 use glifparser::glif::inner::MFEKContourInner;
 use glifparser::glif::{MFEKOutline, MFEKContour};
 use glifparser::glif::contour::MFEKContourCommon;
-use glifrenderer::constants::{self, OUTLINE_STROKE_THICKNESS};
+use glifrenderer::constants::OUTLINE_STROKE_THICKNESS;
 use kurbo::{PathSeg, QuadBez, ParamCurve};
 use skia_safe::{Canvas, Paint, Path, Point};
 use MFEKmath::{Piecewise, Bezier};
@@ -47,7 +47,7 @@ impl Tool for Cut {
         }
     }
 
-    fn draw(&mut self, v: &Editor, i: &Interface, canvas: &Canvas) {
+    fn draw(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
         Self::draw_line(i, v, canvas, &self.start_point, &Some(i.mouse_info.position));
     }
 }
@@ -154,6 +154,8 @@ impl Cut {
     pub fn split_at_intersections(v: &mut Editor, intersections: &[Intersection]) {
         if intersections.len() == 0 { return }
         let mut new_outline = MFEKOutline::new();
+        // Hyper contours aren't cut, and are dropped, so the stacks of those after them move up.
+        let mut sources = Vec::new();
     
         for (ci, contour) in v.get_active_layer_ref().outline.iter().enumerate() {
             let mut added_cuts = Vec::new();
@@ -201,6 +203,7 @@ impl Cut {
                     }
 
                     new_outline.push(new_contour);
+                    sources.push(Some(ci));
                 },
                 glifparser::glif::inner::MFEKContourInnerType::Quad => {
                     let mut new_beziers = Vec::new();
@@ -248,6 +251,7 @@ impl Cut {
                     }
 
                     new_outline.push(mfek_contour);
+                    sources.push(Some(ci));
                 },
                 glifparser::glif::inner::MFEKContourInnerType::Hyper => {},
             }
@@ -255,11 +259,12 @@ impl Cut {
 
         v.begin_modification("Cut", false);
         v.get_active_layer_mut().outline = new_outline;
+        v.remap_operation_stacks(&sources);
         v.end_modification();
     }
     
 
-    pub fn draw_line(i: &Interface, v: &Editor, canvas: &Canvas, start_point: &Option<(f32, f32)>, end_point: &Option<(f32, f32)>) {
+    pub fn draw_line(i: &RenderState, v: &Editor, canvas: &Canvas, start_point: &Option<(f32, f32)>, end_point: &Option<(f32, f32)>) {
         let mut path = Path::new();
         let mut paint = Paint::default();
         let factor = i.viewport.factor;
@@ -274,7 +279,7 @@ impl Cut {
             path.move_to(skpath_start);
             path.line_to(skpath_end);
     
-            paint.set_color(constants::MEASURE_STROKE);
+            paint.set_color(crate::settings::current().colors.measure);
             paint.set_anti_alias(true);
             paint.set_style(skia_safe::PaintStyle::Stroke);
             paint.set_stroke_width(OUTLINE_STROKE_THICKNESS * (1. / factor));
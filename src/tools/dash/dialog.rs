@@ -1,12 +1,55 @@
 use super::super::prelude::*;
+use super::presets::{load_presets, save_presets, DashPreset};
 use super::Dash;
-use crate::user_interface::Interface;
+use crate::user_interface::{InputPrompt, Interface};
 use glifparser::glif::contour_operations::{ContourOperations, dash::DashCull};
 use egui::Ui;
 use skia_safe::{PaintCap, PaintJoin};
 
+/// Reads a pattern of dash and gap lengths, separated by spaces or commas.
+fn parse_dashes(text: &str) -> Result<Vec<f32>, String> {
+    let dashes = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<f32>().map_err(|_| format!("“{}” isn't a length.", s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if dashes.is_empty() {
+        return Err("Enter the length of a dash and of the gap after it.".to_string());
+    }
+    if dashes.iter().any(|d| !d.is_finite() || *d < 0.) {
+        return Err("Lengths can't be negative.".to_string());
+    }
+    if dashes.len() % 2 != 0 {
+        return Err("Every dash needs a gap after it, so enter an even number of lengths.".to_string());
+    }
+    if dashes.iter().sum::<f32>() <= 0. {
+        return Err("The dashes and gaps can't all be zero.".to_string());
+    }
+    Ok(dashes)
+}
+
+fn format_dashes(dashes: &[f32]) -> String {
+    dashes.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(" ")
+}
+
 impl Dash {
-    pub fn tool_dialog(&mut self, v: &mut Editor, _: &Interface, ui: &mut Ui) {
+    fn presets(&mut self, i: &mut Interface) -> &mut Vec<DashPreset> {
+        if self.presets.is_none() {
+            self.presets = Some(load_presets().unwrap_or_else(|message| {
+                i.push_prompt(InputPrompt::Message { message });
+                vec![]
+            }));
+        }
+        self.presets.as_mut().unwrap()
+    }
+
+    fn save_presets(&mut self, i: &mut Interface) {
+        if let Err(message) = save_presets(self.presets(i)) {
+            i.push_prompt(InputPrompt::Message { message });
+        }
+    }
+
+    pub fn tool_dialog(&mut self, v: &mut Editor, i: &mut Interface, ui: &mut Ui) {
         if v.contour_idx.is_none() {
             ui.label("No selection!");
             return;
@@ -19,31 +62,94 @@ impl Dash {
 
         if let Some(ContourOperations::DashAlongPath { mut data }) = operation {
             let original_data = data.clone();
+            let original_options = v.dash_options(contour_idx);
+            let mut options = original_options;
+
+            let mut chosen = None;
+            let presets = self.presets(i);
+            egui::ComboBox::new("dash_preset", "Preset")
+                .selected_text("Apply…")
+                .show_ui(ui, |ui| {
+                    if presets.is_empty() {
+                        ui.label("No saved presets");
+                    }
+                    for preset in presets.iter() {
+                        if ui.selectable_label(false, &preset.name).clicked() {
+                            chosen = Some(preset.clone());
+                        }
+                    }
+                });
+            if let Some(preset) = chosen {
+                data.stroke_width = preset.stroke_width;
+                data.dash_desc = preset.dashes;
+                options = preset.options;
+                self.preset_name = preset.name;
+            }
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.preset_name);
+                let name = self.preset_name.trim().to_string();
+                if ui.button("Save").on_hover_text("Save these dashes as a preset").clicked() {
+                    if name.is_empty() {
+                        i.push_prompt(InputPrompt::Message { message: "Name the preset to save it.".to_string() });
+                    } else {
+                        let preset = DashPreset {
+                            name: name.clone(),
+                            stroke_width: data.stroke_width,
+                            dashes: data.dash_desc.clone(),
+                            options,
+                        };
+                        let presets = self.presets(i);
+                        match presets.iter_mut().find(|p| p.name == name) {
+                            Some(existing) => *existing = preset,
+                            None => presets.push(preset),
+                        }
+                        self.save_presets(i);
+                    }
+                }
+                if ui.button("Delete").on_hover_text("Delete the preset of this name").clicked() {
+                    let presets = self.presets(i);
+                    let before = presets.len();
+                    presets.retain(|p| p.name != name);
+                    if presets.len() == before {
+                        i.push_prompt(InputPrompt::Message { message: format!("There's no preset named “{}”.", name) });
+                    } else {
+                        self.save_presets(i);
+                    }
+                }
+            });
+
+            ui.separator();
 
             ui.label("Stroke Width");
             let width_slider = egui::Slider::new(&mut data.stroke_width, 0.0..=30.);
             ui.add(width_slider);
-        
-            let mut dashes = data.dash_desc
-                .iter()
-                .map(|f| f.to_string())
-                .collect::<Vec<_>>()
-                .join(" ");
 
             ui.label("Dashes");
-            if ui.text_edit_singleline(&mut dashes).changed() && !dashes.as_str().is_empty() {
-                let new = dashes
-                    .as_str()
-                    .split(" ")
-                    .map(|s| s.parse::<f32>())
-                    .filter(|f| f.is_ok())
-                    .map(|o| o.unwrap())
-                    .collect::<Vec<_>>();
-
-                if new.len() > 1 && new.len() % 2 == 0 {
-                    data.dash_desc = new;
+            let response = ui.text_edit_singleline(&mut self.dash_text)
+                .on_hover_text("Lengths of dashes and the gaps after them, e.g. 10 5 2 5");
+            if response.changed() {
+                match parse_dashes(&self.dash_text) {
+                    Ok(dashes) => {
+                        data.dash_desc = dashes;
+                        self.dash_error = None;
+                    }
+                    Err(error) => self.dash_error = Some(error),
                 }
+            } else if !response.has_focus() {
+                self.dash_text = format_dashes(&data.dash_desc);
+                self.dash_error = None;
             }
+            if let Some(error) = &self.dash_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            let pattern_length: f32 = data.dash_desc.iter().sum();
+            ui.label("Phase");
+            ui.add(egui::Slider::new(&mut options.phase, 0.0..=pattern_length.max(1.)))
+                .on_hover_text("How far into the pattern the contour starts");
+            ui.checkbox(&mut options.fit_to_length, "Fit to contour length")
+                .on_hover_text("Stretches the pattern to repeat a whole number of times, so closed contours end cleanly");
             
             let mut cull = data.cull.is_some();
             ui.checkbox(&mut cull, "Cull");
@@ -101,9 +207,10 @@ impl Dash {
                 data.cull = None;
             }
 
-            if data != original_data {
+            if data != original_data || options != original_options {
                 v.begin_modification("Dash dialog modification.", true);
                 v.get_active_layer_mut().outline[contour_idx].set_operation(Some(ContourOperations::DashAlongPath { data }));
+                v.set_dash_options(contour_idx, options);
                 v.end_modification();
             }
         }
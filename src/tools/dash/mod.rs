@@ -1,15 +1,24 @@
 mod dialog;
+mod presets;
 
 use glifparser::glif::contour_operations::ContourOperations;
 use glifparser::glif::contour_operations::dash::DashContour;
 use skia_safe::{PaintCap, PaintJoin};
 
+use self::presets::DashPreset;
 use super::prelude::*;
 use crate::editor::Editor;
 use crate::tool_behaviors::zoom_scroll::ZoomScroll;
 
 #[derive(Clone, Debug, Default)]
-pub struct Dash {}
+pub struct Dash {
+    // The dashes as typed, kept while they're being edited even if they can't be read yet.
+    dash_text: String,
+    dash_error: Option<String>,
+    // Loaded the first time the dialog's shown.
+    presets: Option<Vec<DashPreset>>,
+    preset_name: String,
+}
 
 impl Tool for Dash {
     #[rustfmt::skip]
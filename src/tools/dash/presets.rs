@@ -0,0 +1,78 @@
+// Named dash patterns the user saved, kept in dash_presets.json in the config directory.
+
+use serde_json::{json, Value};
+
+use crate::contour_operations::dashalongpath::DashOptions;
+use crate::settings::CONFIG_PATH;
+
+use std::fs;
+use std::path::PathBuf;
+
+use lazy_static::lazy_static;
+lazy_static! {
+    pub static ref CONFIG_DASH_PRESETS_PATH: PathBuf = {
+        let mut pb = CONFIG_PATH.clone();
+
+        pb.push("dash_presets");
+        pb.set_extension("json");
+        pb
+    };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DashPreset {
+    pub name: String,
+    pub stroke_width: f32,
+    pub dashes: Vec<f32>,
+    pub options: DashOptions,
+}
+
+impl DashPreset {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "strokeWidth": self.stroke_width,
+            "dashes": self.dashes,
+            "phase": self.options.phase,
+            "fitToLength": self.options.fit_to_length,
+        })
+    }
+
+    fn from_json(v: &Value) -> Result<Self, String> {
+        let name = v["name"].as_str().ok_or("A dash preset has no name.")?.to_string();
+        let dashes: Vec<f32> = serde_json::from_value(v["dashes"].clone())
+            .map_err(|e| format!("The dash preset “{}” has no dashes: {}", name, e))?;
+        Ok(Self {
+            stroke_width: v["strokeWidth"].as_f64().unwrap_or(10.) as f32,
+            dashes,
+            options: DashOptions {
+                phase: v["phase"].as_f64().unwrap_or(0.) as f32,
+                fit_to_length: v["fitToLength"].as_bool().unwrap_or(false),
+            },
+            name,
+        })
+    }
+}
+
+/// The saved presets. There are none until one is saved.
+pub fn load_presets() -> Result<Vec<DashPreset>, String> {
+    if !CONFIG_DASH_PRESETS_PATH.exists() {
+        return Ok(vec![]);
+    }
+    let text = fs::read_to_string(&*CONFIG_DASH_PRESETS_PATH)
+        .map_err(|e| format!("Couldn't read `{}`: {}", CONFIG_DASH_PRESETS_PATH.display(), e))?;
+    let presets: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("`{}` isn't valid JSON: {}", CONFIG_DASH_PRESETS_PATH.display(), e))?;
+    presets
+        .as_array()
+        .ok_or_else(|| format!("`{}` isn't a list of presets.", CONFIG_DASH_PRESETS_PATH.display()))?
+        .iter()
+        .map(DashPreset::from_json)
+        .collect()
+}
+
+pub fn save_presets(presets: &[DashPreset]) -> Result<(), String> {
+    let presets = Value::Array(presets.iter().map(DashPreset::to_json).collect());
+    fs::write(&*CONFIG_DASH_PRESETS_PATH, serde_json::to_string_pretty(&presets).unwrap())
+        .map_err(|e| format!("Couldn't save dash presets to `{}`: {}", CONFIG_DASH_PRESETS_PATH.display(), e))
+}
@@ -1,17 +1,61 @@
 
 use super::super::prelude::*;
 use super::Image;
+use crate::filedialog;
 use crate::user_interface::{Interface, gui::windows::egui_parsed_textfield};
 use egui::Ui;
 use glifparser::IntegerOrFloat;
 
 impl Image {
     pub fn tool_dialog(&mut self, v: &mut Editor, _i: &mut Interface, ui: &mut Ui) {
+        let missing = v.missing_images();
+        if !missing.is_empty() {
+            ui.colored_label(egui::Color32::YELLOW, "Missing images");
+            for filename in missing {
+                ui.horizontal(|ui| {
+                    ui.label(filename.to_string_lossy());
+                    if ui.button("Relink…").clicked() {
+                        if let Some(path) = filedialog::open_image(None) {
+                            v.begin_modification("Relinked image.", false);
+                            match v.relink_image(&filename, path) {
+                                Ok(()) => {
+                                    v.end_modification();
+                                    self.error = None;
+                                }
+                                Err(e) => {
+                                    v.cancel_modification();
+                                    self.error = Some(e);
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+            ui.separator();
+        }
+
+        if let Some(error) = self.error.as_ref() {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        // Locked and hidden images can't be clicked, so they're picked from this list instead.
+        let filenames: Vec<_> = v.get_active_layer_ref().images.iter().map(|(im, _)| im.filename.clone()).collect();
+        for (idx, filename) in filenames.iter().enumerate() {
+            let name = filename.file_name().unwrap_or_default().to_string_lossy();
+            if ui.selectable_label(self.selected_idx == Some(idx), name).clicked() {
+                self.selected_idx = Some(idx);
+            }
+        }
+        if !filenames.is_empty() {
+            ui.separator();
+        }
+
         if let Some(selected) = self.selected_idx {
             let mut image = v.get_active_layer_ref().images[selected].clone();
             ui.label("Position");
             image.0.xOffset = IntegerOrFloat::Float(egui_parsed_textfield(ui, "px", image.0.xOffset.into(), &mut self.edit_buf));
             image.0.yOffset = IntegerOrFloat::Float(egui_parsed_textfield(ui, "py", image.0.xOffset.into(), &mut self.edit_buf));
+            ui.label("Tint");
             let o_rgba: [f32; 4] =  v.get_active_layer_ref().images[selected].0.color.unwrap_or_else(|| [1., 1., 1., 1.].into()).into();
             let mut rgba = o_rgba.clone();
             if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
@@ -22,15 +66,59 @@ impl Image {
                 v.recache_images();    
             }
 
+            let o_settings = v.image_settings(v.layer_idx.unwrap(), selected);
+            let mut settings = o_settings;
+            ui.label("Opacity");
+            ui.add(egui::Slider::new(&mut settings.opacity, 0f32..=1f32));
+            ui.checkbox(&mut settings.visible, "Visible");
+            ui.checkbox(&mut settings.locked, "Locked");
+            ui.label("Crop");
+            ui.horizontal(|ui| {
+                for (name, crop) in ["Left", "Top", "Right", "Bottom"].iter().zip(settings.crop.iter_mut()) {
+                    ui.label(*name);
+                    *crop = egui_parsed_textfield(ui, format!("crop_{}", name), *crop, &mut self.edit_buf).max(0.);
+                }
+            });
+            if settings != o_settings {
+                v.begin_modification("Changed image settings.", true);
+                v.set_image_settings(v.layer_idx.unwrap(), selected, settings);
+                v.end_modification();
+            }
+
             if image != v.get_active_layer_ref().images[selected].clone() {
                 v.begin_modification("Edited image with image window.", false);
                 v.get_active_layer_mut().images[selected] = image;
                 v.end_modification()
             }
 
+            ui.separator();
+            ui.collapsing("Trace", |ui| {
+                ui.label("Threshold");
+                ui.add(egui::Slider::new(&mut self.trace.threshold, 0u8..=255u8));
+                ui.label("Corner angle");
+                ui.add(egui::Slider::new(&mut self.trace.corner_angle, 0f32..=180f32).suffix("°"));
+                ui.label("Smoothing tolerance");
+                ui.add(egui::Slider::new(&mut self.trace.tolerance, 0.1f32..=10f32));
+
+                if ui.button("Trace to outlines").clicked() {
+                    v.begin_modification("Traced image.", false);
+                    match v.trace_image(selected, &self.trace) {
+                        Ok(_) => {
+                            v.end_modification();
+                            self.error = None;
+                        }
+                        Err(e) => {
+                            v.cancel_modification();
+                            self.error = Some(e);
+                        }
+                    }
+                }
+            });
+            ui.separator();
+
             if ui.button("Remove Image").clicked() {
                 v.begin_modification("Removed image.", false);
-                v.get_active_layer_mut().images.remove(selected);
+                v.remove_image(selected);
                 v.end_modification();
 
                 self.selected_idx = None;
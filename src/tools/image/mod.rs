@@ -3,22 +3,25 @@ use std::collections::HashMap;
 use super::prelude::*;
 use crate::command::Command;
 use crate::editor::Editor;
+use crate::editor::autotrace::TraceSettings;
 use crate::filedialog;
 use crate::tool_behaviors::{
     move_image::MoveImage, rotate_image::RotateImage, zoom_scroll::ZoomScroll,
 };
-use crate::user_interface::{Interface, MouseInfo};
+use crate::user_interface::{InputPrompt, Interface, MouseInfo};
 use glifparser::matrix::ToSkiaMatrix;
-use skia_safe::{Paint, PaintStyle, Path, Contains};
+use skia_safe::{Contains, EncodedOrigin, Paint, PaintStyle, Path};
 
 mod dialog;
 
 // The image tool is for adding and manipulating images on layers. With the image tool selected you can click an empty space in order
 // to add an image to the current layer. Clicking an image without holding any modifiers translates that image. Clicking while holding
-// ctrl rotates the image around it's center.
+// ctrl rotates the image around it's center. The selected image can also be traced into outlines from the tool's dialog.
 #[derive(Clone, Debug)]
 pub struct Image {
     selected_idx: Option<usize>,
+    trace: TraceSettings,
+    error: Option<String>,
 
     edit_buf: HashMap<String, String>,
 }
@@ -29,7 +32,7 @@ impl Tool for Image {
         match event {
             EditorEvent::MouseEvent { mouse_info, event_type } => {
                 match event_type {
-                    MouseEventType::Pressed => self.mouse_pressed(v, mouse_info),
+                    MouseEventType::Pressed => self.mouse_pressed(v, i, mouse_info),
                     _ => (),
                 }
             },
@@ -41,10 +44,13 @@ impl Tool for Image {
         }
     }
 
-    fn draw(&mut self, v: &Editor, _i: &Interface, canvas: &Canvas) {
+    fn draw(&mut self, v: &Editor, _i: &RenderState, canvas: &Canvas) {
         if let Some(selected) = self.selected_idx {
+            let img_rect = match self.get_image_rect(v, selected) {
+                Some(rect) => rect,
+                None => return,
+            };
             let mut selected_path = Path::new();
-            let img_rect = self.get_image_rect(v, selected);
             selected_path.add_rect(img_rect, None);
 
             let mut paint = Paint::default();
@@ -66,6 +72,8 @@ impl Image {
     pub fn new() -> Self {
         Self { 
             selected_idx: None,
+            trace: TraceSettings::default(),
+            error: None,
             edit_buf: HashMap::new(),
         }
     }
@@ -73,25 +81,14 @@ impl Image {
     fn is_image_clicked(&self, v: &Editor, mouse_info: MouseInfo) -> Option<usize> {
         // we've got to take our current mouse position and translate that into 'image space' such that the mouse position
         // is relative to 0,0 and the image forms an axis aligned bounding box
-        let iter = v.get_active_layer_ref().images.iter().enumerate();
-        for (idx, (l_image, i_matrix)) in iter {
-            let image = &v.images[&l_image.filename];
-
-            let img_rect = image.img.bounds();
-
-            let origin_mat = i_matrix.to_skia_matrix();
-
-            let f_rect = SkRect::new(
-                img_rect.left() as f32,
-                img_rect.top() as f32,
-                img_rect.right() as f32,
-                img_rect.bottom() as f32,
-            );
-            let final_img_rect = origin_mat.map_rect(f_rect).0;
-
-            let local_mouse = SkPoint::new(mouse_info.position.0, mouse_info.position.1);
+        let local_mouse = SkPoint::new(mouse_info.position.0, mouse_info.position.1);
+        for idx in 0..v.get_active_layer_ref().images.len() {
+            let settings = v.image_settings(v.layer_idx.unwrap(), idx);
+            if settings.locked || !settings.visible {
+                continue;
+            }
 
-            if final_img_rect.contains(local_mouse) {
+            if self.get_image_rect(v, idx).map(|r| r.contains(local_mouse)).unwrap_or(false) {
                 return Some(idx);
             }
         }
@@ -110,24 +107,20 @@ impl Image {
         (point.x, point.y)
     }
 
-    fn get_image_rect(&self, v: &Editor, idx: usize) -> SkRect {
+    // The image's bounds in glyph space, less whatever is cropped off. None if the image is missing.
+    fn get_image_rect(&self, v: &Editor, idx: usize) -> Option<SkRect> {
         let layer = v.get_active_layer_ref();
-        let image = &v.images[&layer.images[idx].0.filename];
-
-        let img_rect = image.img.bounds();
+        let image = v.images.get(&layer.images.get(idx)?.0.filename)?;
+        let settings = v.image_settings(v.layer_idx.unwrap(), idx);
 
-        let origin_mat = layer.images[idx].1.to_skia_matrix();
+        let size = (image.img.width(), image.img.height());
+        let origin_mat = layer.images[idx].1.to_skia_matrix()
+            * EncodedOrigin::to_matrix(EncodedOrigin::BottomLeft, size);
 
-        let f_rect = SkRect::new(
-            img_rect.left() as f32,
-            img_rect.top() as f32,
-            img_rect.right() as f32,
-            img_rect.bottom() as f32,
-        );
-        origin_mat.map_rect(f_rect).0
+        Some(origin_mat.map_rect(settings.crop_rect(size)).0)
     }
 
-    fn mouse_pressed(&mut self, v: &mut Editor, mouse_info: MouseInfo) {
+    fn mouse_pressed(&mut self, v: &mut Editor, i: &mut Interface, mouse_info: MouseInfo) {
         // if we did click an image we're going to want to let the user translate/rotate that image
         if let Some(img_idx) = self.is_image_clicked(v, mouse_info) {
             self.selected_idx = Some(img_idx);
@@ -151,8 +144,13 @@ impl Image {
             };
 
             v.begin_modification("Add image to layer.", false);
-            v.add_image_to_active_layer(filename);
-            v.end_modification();
+            match v.add_image_to_active_layer(filename) {
+                Ok(()) => v.end_modification(),
+                Err(e) => {
+                    v.cancel_modification();
+                    i.push_prompt(InputPrompt::Message { message: e });
+                }
+            }
         }
     }
 
@@ -160,8 +158,7 @@ impl Image {
         if let Some(idx) = self.selected_idx {
             v.begin_modification("Delete image from layer.", false);
             self.selected_idx = None;
-            v.get_active_layer_mut().images.remove(idx);
-            v.recache_images();
+            v.remove_image(idx);
             v.end_modification();
         }
     }
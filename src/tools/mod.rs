@@ -4,8 +4,8 @@ pub_mod!("src/tools");
 use self::cut::Cut;
 use self::prelude::*;
 use self::{
-    anchors::Anchors, dash::Dash, guidelines::Guidelines, image::Image, measure::Measure, pan::Pan,
-    pap::PAP, pen::Pen, select::Select, shapes::Shapes, vws::VWS, zoom::Zoom,
+    anchors::Anchors, dash::Dash, guidelines::Guidelines, image::Image, measure::Measure, nib::Nib,
+    pan::Pan, pap::PAP, pen::Pen, select::Select, shapes::Shapes, vws::VWS, zoom::Zoom,
 };
 
 use dyn_clone::DynClone;
@@ -20,7 +20,7 @@ pub trait Tool: DynClone + std::fmt::Debug {
     fn event(&mut self, v: &mut Editor, i: &mut Interface, event: EditorEvent);
 
     // We provide empty default implementations for these two because not every tool needs these hooks.
-    fn draw(&mut self, _v: &Editor, _i: &Interface, _canvas: &Canvas) {}
+    fn draw(&mut self, _v: &Editor, _i: &RenderState, _canvas: &Canvas) {}
 
     // UI hooks. Dialog hooks into the tools dialog.
     fn dialog(&mut self, _v: &mut Editor, _i: &mut Interface, _ui: &mut Ui) -> bool {
@@ -51,6 +51,7 @@ pub enum ToolEnum {
     VWS,
     PAP,
     Dash,
+    Nib,
     Shapes,
     Image,
     Guidelines,
@@ -79,6 +80,7 @@ pub fn tool_enum_to_tool(tool: ToolEnum) -> Box<dyn Tool> {
         ToolEnum::Shapes => Box::new(Shapes::new()),
         ToolEnum::VWS => Box::new(VWS::new()),
         ToolEnum::Dash => Box::new(Dash::new()),
+        ToolEnum::Nib => Box::new(Nib::new()),
         ToolEnum::Image => Box::new(Image::new()),
         ToolEnum::PAP => Box::new(PAP::new()),
         ToolEnum::Guidelines => Box::new(Guidelines::new()),
@@ -0,0 +1,65 @@
+use super::super::prelude::*;
+use super::{nib_stage, Nib};
+use crate::contour_operations::nibstroke::NibShape;
+use crate::contour_operations::stack::StageOperation;
+use crate::user_interface::Interface;
+use egui::Ui;
+use glifparser::glif::contour::MFEKContourCommon as _;
+
+impl Nib {
+    pub fn tool_dialog(&mut self, v: &mut Editor, _: &Interface, ui: &mut Ui) {
+        if v.contour_idx.is_none() {
+            ui.label("No selection!");
+            return;
+        }
+
+        let contour_idx = v.contour_idx.unwrap();
+        let mut stages = v.operation_stack(contour_idx);
+        let (stage_idx, mut data) = match nib_stage(&stages) {
+            Some(nib) => nib,
+            None => return,
+        };
+        let original_data = data.clone();
+
+        egui::ComboBox::from_label("Shape")
+            .selected_text(format!("{:?}", data.shape))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut data.shape, NibShape::Ellipse, "Ellipse");
+                ui.selectable_value(&mut data.shape, NibShape::Rectangle, "Rectangle");
+            });
+
+        ui.label("Width");
+        ui.add(egui::Slider::new(&mut data.width, 1.0..=300.));
+        ui.label("Height");
+        ui.add(egui::Slider::new(&mut data.height, 0.0..=300.));
+
+        let mut per_point = data.point_angles.is_some();
+        ui.checkbox(&mut per_point, "Angle per point")
+            .on_hover_text("Turn the nib from each point to the next");
+        if per_point != data.point_angles.is_some() {
+            let len = v.get_active_layer_ref().outline[contour_idx].len();
+            data.point_angles = per_point.then(|| vec![data.angle; len]);
+        }
+
+        match (data.point_angles.as_mut(), v.point_idx) {
+            (Some(angles), Some(pi)) if pi < angles.len() => {
+                ui.label(format!("Angle at point {}", pi));
+                ui.add(egui::Slider::new(&mut angles[pi], -180.0..=180.).suffix("°"));
+            }
+            (Some(_), _) => {
+                ui.label("Select a point to set its angle.");
+            }
+            (None, _) => {
+                ui.label("Angle");
+                ui.add(egui::Slider::new(&mut data.angle, -180.0..=180.).suffix("°"));
+            }
+        }
+
+        if data != original_data {
+            stages[stage_idx].operation = StageOperation::Nib(data);
+            v.begin_modification("Nib dialog modification.", true);
+            v.set_operation_stack(contour_idx, stages);
+            v.end_modification();
+        }
+    }
+}
@@ -0,0 +1,97 @@
+mod dialog;
+
+use glifparser::glif::contour::MFEKContourCommon as _;
+use MFEKmath::vec2;
+
+use super::prelude::*;
+use crate::constants::NIB_STROKE;
+use crate::contour_operations::nibstroke::NibContour;
+use crate::contour_operations::stack::{Stage, StageOperation};
+use crate::editor::operation_stack::NewStage;
+use crate::editor::Editor;
+use crate::tool_behaviors::zoom_scroll::ZoomScroll;
+
+#[derive(Clone, Debug, Default)]
+pub struct Nib {}
+
+impl Tool for Nib {
+    #[rustfmt::skip]
+    fn event(&mut self, v: &mut Editor, i: &mut Interface, event: EditorEvent) {
+        match event {
+            EditorEvent::MouseEvent { mouse_info, event_type } => match event_type {
+                MouseEventType::Pressed => self.mouse_pressed(v, i, mouse_info),
+                _ => (),
+            }
+            EditorEvent::ScrollEvent { .. } => ZoomScroll::default().event(v, i, event),
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
+        self.draw_nibs(v, i, canvas);
+    }
+
+    fn dialog(&mut self, v: &mut Editor, i: &mut Interface, ui: &mut Ui) -> bool {
+        let show_dialog = match v.contour_idx {
+            Some(ci) => nib_stage(&v.operation_stack(ci)).is_some(),
+            _ => false,
+        };
+
+        if show_dialog {
+            self.tool_dialog(v, i, ui);
+            return true;
+        }
+
+        false
+    }
+}
+
+/// The first nib stroke in a contour's stack, which is the one this tool edits.
+fn nib_stage(stages: &[Stage]) -> Option<(usize, NibContour)> {
+    stages.iter().enumerate().find_map(|(si, stage)| match &stage.operation {
+        StageOperation::Nib(nib) => Some((si, nib.clone())),
+        _ => None,
+    })
+}
+
+impl Nib {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn mouse_pressed(&mut self, v: &mut Editor, i: &mut Interface, mouse_info: MouseInfo) {
+        if let Some((ci, pi, _wh)) = clicked_point_or_handle(v, i, mouse_info.raw_position, None) {
+            v.contour_idx = Some(ci);
+            v.point_idx = Some(pi);
+
+            let mut stages = v.operation_stack(ci);
+            if nib_stage(&stages).is_none() {
+                v.begin_modification("Added nib stroke.", false);
+                stages.push(v.new_stage(NewStage::Nib, ci));
+                v.set_operation_stack(ci, stages);
+                v.end_modification();
+            }
+        }
+    }
+
+    /// Draws the nib where it's put down at each point of the contours it strokes.
+    fn draw_nibs(&self, v: &Editor, i: &RenderState, canvas: &Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(1. / i.viewport.factor);
+        paint.set_color(NIB_STROKE);
+
+        for (ci, contour) in v.get_active_layer_ref().outline.iter().enumerate() {
+            let nib = match nib_stage(&v.operation_stack(ci)) {
+                Some((_, nib)) => nib,
+                None => continue,
+            };
+            for pi in 0..contour.len() {
+                let point = contour.get_point(pi).unwrap();
+                let path = nib.nib_path(vec2![point.x(), point.y()], nib.angle_at(pi));
+                canvas.draw_path(&path, &paint);
+            }
+        }
+    }
+}
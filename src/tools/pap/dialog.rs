@@ -1,7 +1,9 @@
 use super::super::prelude::*;
 use super::PAP;
-use crate::user_interface::Interface;
+use crate::user_interface::{InputPrompt, Interface};
 use glifparser::glif::contour_operations::{pap::{PatternStretch, PatternCopies, PatternSubdivide, PAPContour, PatternCulling}, ContourOperations};
+use glifparser::glif::MFEKOutline;
+use glifparser::MFEKPointData;
 use egui::Ui;
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -18,8 +20,31 @@ enum PAPCulling {
     EraseOverlapping,
 }
 
+// TODO: Default() implementation for many of our structs.
+fn new_pap_contour(pattern: MFEKOutline<MFEKPointData>) -> PAPContour<MFEKPointData> {
+    PAPContour {
+        pattern,
+        copies: PatternCopies::Repeated,
+        subdivide: PatternSubdivide::Off,
+        is_vertical: false,
+        stretch: PatternStretch::On,
+        spacing: 4.,
+        simplify: false,
+        normal_offset: 0.,
+        tangent_offset: 0.,
+        pattern_scale: (1., 1.),
+        center_pattern: true,
+        prevent_overdraw: PatternCulling::Off,
+        two_pass_culling: false,
+        reverse_path: false,
+        reverse_culling: false,
+        warp_pattern: true,
+        split_path: false,
+    }
+}
+
 impl PAP {
-    pub fn tool_dialog(&mut self, v: &mut Editor, _i: &Interface, ui: &mut Ui) {
+    pub fn tool_dialog(&mut self, v: &mut Editor, i: &mut Interface, ui: &mut Ui) {
         if v.contour_idx.is_none() { return };
         let contour_idx = v.contour_idx.expect("Checked in function return gaurd.");
 
@@ -31,6 +56,23 @@ impl PAP {
         if let Some(ContourOperations::PatternAlongPath { mut data }) = operation {
             let original_data = data.clone();
 
+            if let Some(name) = v.pattern_glyph(contour_idx) {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Pattern: {}", name));
+                    if ui.button("Reload").clicked() {
+                        match v.sibling_pattern(&name) {
+                            Ok(pattern) => data.pattern = pattern,
+                            Err(message) => i.push_prompt(InputPrompt::Message { message }),
+                        }
+                    }
+                    if ui.button("Unlink").on_hover_text("Keep the pattern as it is now, and stop following the glyph").clicked() {
+                        v.begin_modification("Unlinked PAP pattern.", true);
+                        v.set_pattern_glyph(contour_idx, None);
+                        v.end_modification();
+                    }
+                });
+            }
+
             egui::ComboBox::from_label("Mode")
                 .selected_text(format!("{:?}", data.copies))
                 .show_ui(ui, |ui| {
@@ -193,31 +235,32 @@ impl PAP {
                 let pattern = v.with_glyph(|glif| glif.layers[self.pattern_layer.unwrap()].clone()).outline;
                 v.begin_modification("Added PAP contour.", true);
                 v.get_active_layer_mut().outline[contour_idx].set_operation(
-                    Some(ContourOperations::PatternAlongPath {
-                        // TODO: Default() implementation for many of our structs.
-                        data: PAPContour {
-                            pattern: pattern,
-                            copies: PatternCopies::Repeated,
-                            subdivide: PatternSubdivide::Off,
-                            is_vertical: false,
-                            stretch: PatternStretch::On,
-                            spacing: 4.,
-                            simplify: false,
-                            normal_offset: 0.,
-                            tangent_offset: 0.,
-                            pattern_scale: (1., 1.),
-                            center_pattern: true,
-                            prevent_overdraw: PatternCulling::Off,
-                            two_pass_culling: false,
-                            reverse_path: false,
-                            reverse_culling: false,
-                            warp_pattern: true,
-                            split_path: false,
-                        },
-                    })
+                    Some(ContourOperations::PatternAlongPath { data: new_pap_contour(pattern) })
                 );
+                v.set_pattern_glyph(contour_idx, None);
                 v.end_modification();
             }
+
+            ui.separator();
+
+            ui.label("Pattern Glyph")
+                .on_hover_text("Another glyph in this UFO; the pattern follows it as it's edited");
+            ui.text_edit_singleline(&mut self.pattern_glyph);
+            let enabled = !self.pattern_glyph.trim().is_empty();
+            if ui.add_enabled(enabled, egui::Button::new("Create Pattern Along Path from Glyph.")).clicked() {
+                let name = self.pattern_glyph.trim().to_string();
+                match v.sibling_pattern(&name) {
+                    Ok(pattern) => {
+                        v.begin_modification("Added PAP contour.", true);
+                        v.get_active_layer_mut().outline[contour_idx].set_operation(
+                            Some(ContourOperations::PatternAlongPath { data: new_pap_contour(pattern) })
+                        );
+                        v.set_pattern_glyph(contour_idx, Some(name));
+                        v.end_modification();
+                    }
+                    Err(message) => i.push_prompt(InputPrompt::Message { message }),
+                }
+            }
         } else {
             ui.label("No selection!");
         }
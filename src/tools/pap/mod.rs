@@ -8,6 +8,8 @@ use crate::editor::Editor;
 #[derive(Clone, Debug)]
 pub struct PAP {
     pattern_layer: Option<usize>,
+    /// Name of the glyph typed in to take the pattern from.
+    pattern_glyph: String,
     select_tool: Select
 }
 
@@ -46,6 +48,7 @@ impl PAP {
     pub fn new() -> Self {
         Self {
             pattern_layer: None,
+            pattern_glyph: String::new(),
             select_tool: Select::new()
         }
     }
@@ -76,7 +76,7 @@ impl PenMode for CubicMode {
         v.push_behavior(Box::new(MoveHandle::new(WhichHandle::A, mouse_info, true)));
     }
 
-    fn draw_nearest_point(&self, i: &crate::user_interface::Interface, canvas: &Canvas, info: HoveredPointInfo) {
+    fn draw_nearest_point(&self, i: &crate::user_interface::RenderState, canvas: &Canvas, info: HoveredPointInfo) {
         draw_point::<()>(
             &i.viewport,
             &Point::from_x_y_type(info.point, PointType::Curve),
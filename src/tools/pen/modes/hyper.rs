@@ -68,7 +68,7 @@ impl PenMode for HyperMode {
         v.push_behavior(Box::new(MoveHandle::new(WhichHandle::A, mouse_info, true)));
     }
 
-    fn draw_nearest_point(&self, _i: &crate::user_interface::Interface, _canvas: &Canvas, _info: HoveredPointInfo) {
+    fn draw_nearest_point(&self, _i: &crate::user_interface::RenderState, _canvas: &Canvas, _info: HoveredPointInfo) {
     }
     
     fn subdivide_curve(&self, _v: &mut Editor, _info: HoveredPointInfo) {
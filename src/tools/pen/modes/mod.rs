@@ -4,7 +4,7 @@ pub mod hyper;
 
 use MFEKmath::skia_safe::Canvas;
 use dyn_clone::DynClone;
-use crate::{editor::{Editor, util::HoveredPointInfo}, user_interface::{Interface, MouseInfo}};
+use crate::{editor::{Editor, util::HoveredPointInfo}, user_interface::{Interface, MouseInfo, RenderState}};
 
 pub trait PenMode: DynClone + std::fmt::Debug {
     // No selection starting to draw a new contour.
@@ -16,5 +16,5 @@ pub trait PenMode: DynClone + std::fmt::Debug {
     // If a contour type does not support subdivision the way that cubic or quadratic does then you can safely
     // stub these functions out.
     fn subdivide_curve(&self, v: &mut Editor, info: HoveredPointInfo);
-    fn draw_nearest_point(&self, i: &Interface, canvas: &Canvas, info: HoveredPointInfo);
+    fn draw_nearest_point(&self, i: &RenderState, canvas: &Canvas, info: HoveredPointInfo);
 }
\ No newline at end of file
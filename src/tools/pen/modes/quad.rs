@@ -85,6 +85,6 @@ impl PenMode for QuadMode {
 
     // TODO: Implement these for quadratic! Would take a bit more work in math.rlib
     // These functions are safe to be stubbed for now
-    fn draw_nearest_point(&self, _i: &crate::user_interface::Interface, _canvas: &Canvas, _info: HoveredPointInfo) {}
+    fn draw_nearest_point(&self, _i: &crate::user_interface::RenderState, _canvas: &Canvas, _info: HoveredPointInfo) {}
     fn subdivide_curve(&self, _v: &mut Editor, _info: HoveredPointInfo) {}
 }
@@ -23,6 +23,6 @@ pub use skia_safe::{Paint, PaintStyle};
 pub use glifparser::WhichHandle;
 
 //UI
-pub use crate::user_interface::{Interface, MouseInfo};
+pub use crate::user_interface::{Interface, MouseInfo, RenderState};
 pub use egui::Ui;
 pub use sdl2::mouse::MouseButton;
@@ -3,8 +3,10 @@ use std::collections::HashSet;
 // Select
 use super::{prelude::*, EditorEvent, MouseEventType, Tool};
 use crate::command::{Command, CommandType};
+use crate::editor::metrics::{clicked_sidebearing_handle, draw_sidebearing_handles};
 use crate::editor::tunni::{get_closest_tunni_line, get_tunni_line_from_info, get_tunni_point_from_info, clicked_tunni_point_or_line};
 use crate::get_point_mut;
+use crate::tool_behaviors::move_sidebearing::MoveSidebearing;
 use crate::tool_behaviors::move_tunni_point::MoveTunniPoint;
 use crate::tool_behaviors::rotate_selection::RotateSelection;
 use crate::tool_behaviors::move_tunni_line::MoveTunniLine;
@@ -70,7 +72,8 @@ impl Tool for Select {
         }
     }
 
-    fn draw(&mut self, v: &Editor, i: &Interface, canvas: &Canvas) {
+    fn draw(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
+        draw_sidebearing_handles(v, i, canvas);
         self.draw_tunni_line(v, i, canvas);
         self.draw_pivot.draw(v, i, canvas);
     }
@@ -91,7 +94,7 @@ impl Select {
         v.selected = points;
     }
 
-    fn draw_tunni_line(&mut self, v: &Editor, i: &Interface, canvas: &Canvas) {
+    fn draw_tunni_line(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
         let mut paint = Paint::default();
     
         let closest_tunni = get_closest_tunni_line(v, i);
@@ -239,6 +242,13 @@ impl Select {
             }
         }
 
+        // the user clicked one of the handles on the baseline so we're going to drag a sidebearing
+        if mouse_info.button == MouseButton::Left {
+            if let Some(side) = clicked_sidebearing_handle(v, i, mouse_info) {
+                v.set_behavior(Box::new(MoveSidebearing::new(side, mouse_info)));
+                return
+            }
+        }
 
         // the user clicked an empty location
         // if the user isn't holding shift we clear the current selection and the currently selected
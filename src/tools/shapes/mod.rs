@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::f32::consts::PI;
 
 use super::prelude::*;
+use crate::editor::operation_stack::splice_sources;
 use crate::tool_behaviors::draw_pivot::DrawPivot;
 use crate::tool_behaviors::selection_box::SelectionBox;
 use crate::user_interface::Interface;
@@ -57,7 +58,7 @@ impl Tool for Shapes {
         true
     }
 
-    fn draw(&mut self, v: &Editor, i: &Interface, canvas: &Canvas) {
+    fn draw(&mut self, v: &Editor, i: &RenderState, canvas: &Canvas) {
         if let Some(corners) = self.corners {
             SelectionBox::draw_box_impl(i, canvas, corners);
         }
@@ -352,9 +353,10 @@ impl Shapes {
         if let Some(pos) = self.pressed_pos {
             self.locked_angle = mouse_info.modifiers.ctrl;
             if self.dropped_shape {
-                let layer = v.get_active_layer_mut();
-                if layer.outline.len() > 0 {
-                    layer.outline.remove(layer.outline.len() - 1);
+                let count = v.get_active_layer_ref().outline.len();
+                if count > 0 {
+                    v.get_active_layer_mut().outline.remove(count - 1);
+                    v.remap_operation_stacks(&splice_sources(count, count - 1, 1, 0));
                 }
             }
 
@@ -1,15 +1,17 @@
 use glifparser::CapType;
 use glifparser::JoinType;
-use glifparser::glif::contour_operations::ContourOperations;
+use glifparser::glif::contour::MFEKContourCommon as _;
+use glifparser::glif::contour_operations::{vws::VWSHandle, ContourOperations};
 
 use super::super::prelude::*;
 use super::util::*;
 use super::VWS;
-use crate::user_interface::Interface;
+use crate::contour_operations::variablewidthstroke::{WidthInterpolation, WidthProfile};
+use crate::user_interface::{InputPrompt, Interface};
 use crate::user_interface::gui::windows::egui_parsed_textfield;
 
 impl VWS {
-    pub fn tool_dialog(&mut self, v: &mut Editor, _i: &Interface, ui: &mut egui::Ui) {
+    pub fn tool_dialog(&mut self, v: &mut Editor, i: &mut Interface, ui: &mut egui::Ui) {
         if v.contour_idx.is_none() {
             ui.label("No selection!");
             return;
@@ -19,6 +21,14 @@ impl VWS {
         let operation = v.get_active_layer_ref().outline[cidx].operation().clone();
         if let Some(ContourOperations::VariableWidthStroke { mut data }) = operation {
             let original_data = data.clone();
+            let (len, closed) = {
+                let contour = &v.get_active_layer_ref().outline[cidx];
+                (contour.len(), contour.is_closed())
+            };
+            let curves = if closed { len } else { len.saturating_sub(1) };
+            let mut interpolation = v.width_interpolation(cidx);
+            interpolation.resize(curves, WidthInterpolation::Linear);
+            let original_interpolation = interpolation.clone();
 
             ui.collapsing("Contour", |ui| {
                 ui.label("Cap Types");
@@ -69,13 +79,94 @@ impl VWS {
                 data.handles[pidx].right_offset = egui_parsed_textfield(ui, "ro", data.handles[pidx].right_offset, &mut self.edit_buf);
             });
 
-            if data != original_data && !v.is_modifying() {
+            ui.collapsing("Widths", |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Copy widths").clicked() {
+                        v.copy_width_profile(&WidthProfile {
+                            handles: data.handles.clone(),
+                            interpolation: interpolation.clone(),
+                        });
+                    }
+                    if ui.button("Paste widths").clicked() {
+                        match v.paste_width_profile() {
+                            Ok(profile) => {
+                                let profile = profile.resample(len);
+                                data.handles = profile.handles;
+                                interpolation = profile.interpolation;
+                                interpolation.resize(curves, WidthInterpolation::Linear);
+                            }
+                            Err(message) => i.push_prompt(InputPrompt::Message { message }),
+                        }
+                    }
+                });
+
+                self.widths_table(ui, &mut data.handles, &mut interpolation, pidx, closed);
+            });
+
+            if (data != original_data || interpolation != original_interpolation) && !v.is_modifying() {
                 v.begin_modification("VWS dialog modification.", true);
                 set_vws_contour(v, cidx, data);
+                v.set_width_interpolation(cidx, interpolation);
                 v.end_modification();
             }
         } else {
             ui.label("Non-VWS contour selected!");
         }
     }
+
+    /// Every point's widths and tangent offset, and how the widths change to the next point's.
+    fn widths_table(
+        &mut self,
+        ui: &mut egui::Ui,
+        handles: &mut [VWSHandle],
+        interpolation: &mut [WidthInterpolation],
+        selected: usize,
+        closed: bool,
+    ) {
+        // The contour's points, without the handle closed contours have for where they end.
+        let points = handles.len().saturating_sub(1);
+        egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+            ui.spacing_mut().text_edit_width = 48.;
+            egui::Grid::new("vws_widths").striped(true).show(ui, |ui| {
+                ui.label("#");
+                ui.label("Left");
+                ui.label("Right");
+                ui.label("Tangent");
+                ui.label("To next");
+                ui.end_row();
+
+                for hi in 0..points {
+                    if hi == selected {
+                        ui.strong(hi.to_string());
+                    } else {
+                        ui.label(hi.to_string());
+                    }
+                    let handle = &mut handles[hi];
+                    handle.left_offset = egui_parsed_textfield(ui, format!("wlo{}", hi), handle.left_offset, &mut self.edit_buf);
+                    handle.right_offset = egui_parsed_textfield(ui, format!("wro{}", hi), handle.right_offset, &mut self.edit_buf);
+                    handle.tangent_offset = egui_parsed_textfield(ui, format!("wto{}", hi), handle.tangent_offset, &mut self.edit_buf);
+                    match interpolation.get_mut(hi) {
+                        Some(mode) => {
+                            egui::ComboBox::from_id_source(format!("winterp{}", hi))
+                                .selected_text(mode.name())
+                                .show_ui(ui, |ui| {
+                                    for choice in [WidthInterpolation::Linear, WidthInterpolation::Smooth, WidthInterpolation::Constant] {
+                                        ui.selectable_value(mode, choice, choice.name());
+                                    }
+                                });
+                        }
+                        None => {
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        // Closed contours end where they start, so they end with the first point's widths too.
+        if closed && points > 0 {
+            handles[points] = handles[0];
+        }
+    }
 }
@@ -264,7 +264,7 @@ fn generate_vws_contour(v: &Editor, contour_idx: usize) -> VWSContour {
 
 pub fn clicked_handle(
     v: & Editor,
-    i: &Interface,
+    i: &RenderState,
     meta: MouseInfo,
 ) -> Option<(usize, usize, WhichHandle)> {
     let factor = i.viewport.factor;
@@ -7,7 +7,7 @@ pub const KNIFE: &str = "\u{F000}";
 pub const MEASURE: &str = "\u{F001}";
 pub const PAN: &str = "\u{F002}";
 pub const PEN: &str = "\u{F003}";
-pub const _PENCIL: &str = "\u{F004}";
+pub const PENCIL: &str = "\u{F004}";
 pub const SELECT: &str = "\u{F005}";
 pub const SHAPES: &str = "\u{F006}";
 pub const _TEXT: &str = "\u{F007}";
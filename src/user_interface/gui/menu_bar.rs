@@ -1,7 +1,13 @@
 use egui::Context;
 use glifrenderer::toggles::PreviewMode;
 
-use crate::{editor::Editor, filedialog, user_interface::Interface};
+use crate::{
+    editor::{clipboard::ClipboardFormat, Editor},
+    filedialog,
+    user_interface::{InputPrompt, Interface},
+};
+
+use std::fs;
 
 use super::window::{GlifWindow, WindowManager};
 
@@ -24,6 +30,29 @@ pub fn menu_bar(ctx: &Context, v: &mut Editor, i: &mut Interface, wm: &mut Windo
                 if ui.button("Export").clicked() {
                     v.export_glif(Some(i)).expect("Error while exporting glif.");
                 }
+                ui.separator();
+                if ui.button("Import SVG…").clicked() {
+                    if let Some(f) = filedialog::open_filename(Some("svg"), None) {
+                        v.begin_modification("Imported SVG.", false);
+                        match v.import_svg(&f, None) {
+                            Ok(_) => v.end_modification(),
+                            Err(e) => {
+                                v.cancel_modification();
+                                i.push_prompt(InputPrompt::Message { message: e });
+                            }
+                        }
+                    }
+                }
+                if ui.button("Export SVG…").clicked() {
+                    export_svg(v, i, false);
+                }
+                if ui
+                    .add_enabled(!v.selected.is_empty(), egui::Button::new("Export Selection as SVG…"))
+                    .clicked()
+                {
+                    export_svg(v, i, true);
+                }
+                ui.separator();
                 if ui.button("Exit").clicked() {
                     v.quit(i);
                 }
@@ -39,6 +68,58 @@ pub fn menu_bar(ctx: &Context, v: &mut Editor, i: &mut Interface, wm: &mut Windo
                 if ui.button("Redo").clicked() {
                     v.redo();
                 }
+                ui.separator();
+                ui.add_enabled_ui(!v.selected.is_empty() || v.point_idx.is_some(), |ui| {
+                    if ui.button("Copy").clicked() {
+                        v.copy_selection();
+                    }
+                    if ui.button("Copy as GLIF").clicked() {
+                        v.copy_selection_as(ClipboardFormat::Glif);
+                    }
+                    if ui.button("Copy as SVG").clicked() {
+                        v.copy_selection_as(ClipboardFormat::Svg);
+                    }
+                });
+                if ui.button("Paste").clicked() {
+                    v.paste_selection(None);
+                }
+                ui.separator();
+                ui.add_enabled_ui(!v.selected.is_empty() || v.point_idx.is_some(), |ui| {
+                    if ui
+                        .button("Harmonize")
+                        .on_hover_text("Make smooth points curvature continuous by changing their handles' lengths")
+                        .clicked()
+                    {
+                        v.harmonize_selection(false);
+                    }
+                    if ui
+                        .button("Harmonize Moving Points")
+                        .on_hover_text("Make smooth points curvature continuous by sliding them between their handles")
+                        .clicked()
+                    {
+                        v.harmonize_selection(true);
+                    }
+                    if ui.button("Balance Handles").clicked() {
+                        v.balance_selection();
+                    }
+                    ui.separator();
+                    if ui
+                        .button("Expand Contour Operations")
+                        .on_hover_text("Replace the selected contour with what its operations make of it")
+                        .clicked()
+                    {
+                        if let Some(ci) = v.contour_idx {
+                            v.expand_contour_operations(ci);
+                        }
+                    }
+                });
+                ui.separator();
+                if ui.button("Preferences…").clicked() {
+                    wm.preferences.set_open(true);
+                }
+                if ui.button("Key Bindings…").clicked() {
+                    wm.keybindings.set_open(true);
+                }
             });
 
             //
@@ -69,8 +150,21 @@ pub fn menu_bar(ctx: &Context, v: &mut Editor, i: &mut Interface, wm: &mut Windo
                 });
 
                 ui.checkbox(&mut i.grid.show, "Grid");
-                ui.checkbox(&mut i.curvature_vis, "Curvature Visualization");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut i.curvature_comb.enabled, "Curvature Comb");
+                    if ui.small_button("…").on_hover_text("Curvature comb settings").clicked() {
+                        wm.curvature_comb.set_open(true);
+                    }
+                });
                 ui.checkbox(&mut i.measure.enabled, "Show Measure");
+                ui.add_enabled_ui(v.font_dir().is_some(), |ui| {
+                    ui.checkbox(&mut i.kerning_preview.enabled, "Kerning Preview")
+                        .on_disabled_hover_text("Only available when editing a glyph in a UFO");
+                    if i.kerning_preview.enabled {
+                        ui.text_edit_singleline(&mut i.kerning_preview.text)
+                            .on_hover_text("Use /name for a glyph by name and /? for this glyph");
+                    }
+                });
             });
 
             //
@@ -84,7 +178,29 @@ pub fn menu_bar(ctx: &Context, v: &mut Editor, i: &mut Interface, wm: &mut Windo
                 let mut grid_open = wm.grid.open();
                 ui.checkbox(&mut grid_open, "Grid");
                 wm.grid.set_open(grid_open);
+
+                let mut metrics_open = wm.metrics.open();
+                ui.checkbox(&mut metrics_open, "Metrics");
+                wm.metrics.set_open(metrics_open);
+
+                let mut glyph_open = wm.glyph.open();
+                ui.checkbox(&mut glyph_open, "Glyph");
+                wm.glyph.set_open(glyph_open);
+
+                let mut curvature_comb_open = wm.curvature_comb.open();
+                ui.checkbox(&mut curvature_comb_open, "Curvature Comb");
+                wm.curvature_comb.set_open(curvature_comb_open);
             })
         })
     });
 }
+
+fn export_svg(v: &Editor, i: &mut Interface, selection_only: bool) {
+    if let Some(f) = filedialog::save_filename(Some("svg"), None) {
+        if let Err(e) = fs::write(&f, v.export_svg(selection_only)) {
+            i.push_prompt(InputPrompt::Message {
+                message: format!("Couldn't write {}: {}", f.display(), e),
+            });
+        }
+    }
+}
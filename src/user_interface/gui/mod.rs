@@ -36,6 +36,11 @@ pub fn build_ui(
         wm.inspector.build(ctx, v, i);
         wm.grid.build(ctx, v, i);
         wm.tool.build(ctx, v, i);
+        wm.metrics.build(ctx, v, i);
+        wm.glyph.build(ctx, v, i);
+        wm.curvature_comb.build(ctx, v, i);
+        wm.keybindings.build(ctx, v, i);
+        wm.preferences.build(ctx, v, i);
 
         if i.active_prompts() {
             prompts::build_and_check_prompts(v, i, ctx);
@@ -65,5 +65,24 @@ pub fn build_and_check_prompts(v: &mut Editor, i: &mut Interface, ctx: &egui::Co
                     })
                 });
         }
+        InputPrompt::Message { message } => {
+            egui::Window::new("MFEKglif")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(Align2::CENTER_CENTER, [0., 0.])
+                .fixed_pos(egui::Pos2::new(
+                    (i.viewport.winsize.0 / 2.) * i.os_dpi(),
+                    (i.viewport.winsize.1 / 2.) * i.os_dpi(),
+                ))
+                .show(ctx, |ui| {
+                    ui.label(message);
+
+                    ui.vertical_centered(|ui| {
+                        if ui.button("OK").clicked() {
+                            i.pop_prompt();
+                        }
+                    });
+                });
+        }
     }
 }
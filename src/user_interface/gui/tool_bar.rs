@@ -58,6 +58,7 @@ pub fn tool_bar(ctx: &Context, v: &mut Editor, _i: &mut Interface) {
                 build_button(v, ui, icons::VWS, ToolEnum::VWS);
                 build_button(v, ui, icons::PAP, ToolEnum::PAP);
                 build_button(v, ui, icons::DASH, ToolEnum::Dash);
+                build_button(v, ui, icons::PENCIL, ToolEnum::Nib);
                 ui.separator();
                 build_button(v, ui, icons::ANCHOR, ToolEnum::Anchors);
                 build_button(v, ui, icons::SHAPES, ToolEnum::Shapes);
@@ -3,14 +3,24 @@ use egui::Context;
 use crate::user_interface::gui::windows::inspection_window::InspectionWindow;
 use crate::{editor::Editor, user_interface::Interface};
 
+use super::windows::curvature_comb_window::CurvatureCombWindow;
+use super::windows::glyph_window::GlyphWindow;
 use super::windows::grid_window::GridWindow;
+use super::windows::keybindings_window::KeybindingsWindow;
 use super::windows::layer_list::LayerList;
+use super::windows::metrics_window::MetricsWindow;
+use super::windows::preferences_window::PreferencesWindow;
 use super::windows::tool_window::ToolWindow;
 pub struct WindowManager {
     pub inspector: InspectionWindow,
     pub grid: GridWindow,
     pub tool: ToolWindow,
     pub layer_list: LayerList,
+    pub metrics: MetricsWindow,
+    pub glyph: GlyphWindow,
+    pub curvature_comb: CurvatureCombWindow,
+    pub keybindings: KeybindingsWindow,
+    pub preferences: PreferencesWindow,
 }
 
 impl WindowManager {
@@ -20,6 +30,11 @@ impl WindowManager {
             grid: GridWindow::new(),
             tool: ToolWindow::new(),
             layer_list: LayerList::new(),
+            metrics: MetricsWindow::new(),
+            glyph: GlyphWindow::new(),
+            curvature_comb: CurvatureCombWindow::new(),
+            keybindings: KeybindingsWindow::new(),
+            preferences: PreferencesWindow::new(),
         }
     }
 }
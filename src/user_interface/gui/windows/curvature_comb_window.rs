@@ -0,0 +1,53 @@
+use crate::{
+    editor::Editor,
+    user_interface::{gui::window::GlifWindow, Interface},
+};
+use egui::Context;
+
+pub struct CurvatureCombWindow {
+    // is this window open?
+    open: bool,
+}
+
+impl CurvatureCombWindow {
+    pub fn new() -> Self {
+        Self { open: false }
+    }
+}
+
+impl GlifWindow for CurvatureCombWindow {
+    fn open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    fn build(&mut self, ctx: &Context, _v: &mut Editor, i: &mut Interface) {
+        let comb = &mut i.curvature_comb;
+        egui::Window::new("Curvature Comb")
+            .resizable(true)
+            .collapsible(true)
+            .open(&mut self.open)
+            .constrain(true)
+            .default_width(100.)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut comb.enabled, "Active");
+                ui.checkbox(&mut comb.all_contours, "All contours")
+                    .on_hover_text("Otherwise only contours with selected points get a comb");
+
+                ui.separator();
+
+                ui.label("Density");
+                ui.add(egui::Slider::new(&mut comb.density, 4..=128).text("teeth per curve"));
+                ui.label("Scale");
+                ui.add(egui::Slider::new(&mut comb.scale, 1000f32..=200000f32).logarithmic(true));
+
+                ui.separator();
+
+                ui.checkbox(&mut comb.mark_discontinuities, "Mark discontinuities")
+                    .on_hover_text("Circles smooth joins where curvature jumps, and dots inflections");
+            });
+    }
+}
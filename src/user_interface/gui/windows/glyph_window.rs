@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use super::egui_parsed_textfield;
+use crate::{
+    editor::Editor,
+    user_interface::{gui::window::GlifWindow, Interface},
+};
+use egui::Context;
+use glifparser::glif::Lib;
+
+/// Parses a list of codepoints like "0041 U+00C1, 0x1E00" into characters.
+pub fn parse_unicodes(s: &str) -> Result<Vec<char>, String> {
+    let mut ret = vec![];
+    for cp in s.split(|c: char| c == ',' || c.is_whitespace()) {
+        if cp.is_empty() {
+            continue;
+        }
+        let hex = cp
+            .trim_start_matches("U+")
+            .trim_start_matches("u+")
+            .trim_start_matches("0x");
+        let c = u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("{} is not a hexadecimal codepoint", cp))?;
+        if ret.contains(&c) {
+            return Err(format!("U+{:04X} is listed twice", c as u32));
+        }
+        ret.push(c);
+    }
+    Ok(ret)
+}
+
+pub fn format_unicodes(unicodes: &[char]) -> String {
+    unicodes
+        .iter()
+        .map(|c| format!("{:04X}", *c as u32))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn parse_unicodes_test() {
+    assert_eq!(parse_unicodes("0041 U+00C1,0x1E00"), Ok(vec!['A', 'Á', 'Ḁ']));
+    assert_eq!(parse_unicodes(""), Ok(vec![]));
+    assert!(parse_unicodes("0041 0041").is_err());
+    assert!(parse_unicodes("D800").is_err());
+    assert!(parse_unicodes("xyz").is_err());
+    assert_eq!(format_unicodes(&['A', 'Á']), "0041 00C1");
+}
+
+pub struct GlyphWindow {
+    // is this window open?
+    open: bool,
+    edit_buf: HashMap<String, String>,
+    // codepoints used by other glyphs in the UFO, read when the window is first drawn
+    font_unicodes: Option<HashMap<char, String>>,
+    error: Option<String>,
+    new_lib_key: String,
+}
+
+impl GlyphWindow {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            edit_buf: HashMap::new(),
+            font_unicodes: None,
+            error: None,
+            new_lib_key: String::new(),
+        }
+    }
+
+    fn set_name(&mut self, v: &mut Editor, name: String) {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            self.error = Some("Glyph names can't be empty or contain spaces.".to_string());
+            return;
+        }
+        self.error = v.rename_glyph(&name).err();
+    }
+
+    fn set_unicodes(&mut self, v: &mut Editor, unicodes: String) {
+        match parse_unicodes(&unicodes) {
+            Ok(unicodes) => {
+                self.error = None;
+                v.begin_modification("Changed glyph's Unicode codepoints.", false);
+                v.with_glyph_mut(|glyph| glyph.unicode = unicodes.clone());
+                v.end_modification();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn build_lib(&mut self, ui: &mut egui::Ui, v: &mut Editor) {
+        let dict = match v.with_glyph(|glyph| glyph.lib.clone()) {
+            Lib::Plist(dict) => dict,
+            Lib::None => plist::Dictionary::new(),
+            _ => {
+                ui.label("This glyph's lib can't be edited here.");
+                return;
+            }
+        };
+
+        let mut new_dict = dict.clone();
+        for (key, value) in dict.iter() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove key").clicked() {
+                    new_dict.remove(key);
+                }
+                ui.label(key);
+                // Only strings and numbers can be edited in place; anything more complex is
+                // shown but left alone.
+                match value {
+                    plist::Value::String(s) => {
+                        let id = format!("lib_{}", key);
+                        let ns = egui_parsed_textfield(ui, id, s.clone(), &mut self.edit_buf);
+                        if ns != *s {
+                            new_dict.insert(key.clone(), plist::Value::String(ns));
+                        }
+                    }
+                    plist::Value::Real(r) => {
+                        let id = format!("lib_{}", key);
+                        let nr = egui_parsed_textfield(ui, id, *r, &mut self.edit_buf);
+                        if nr != *r {
+                            new_dict.insert(key.clone(), plist::Value::Real(nr));
+                        }
+                    }
+                    _ => {
+                        ui.label(format!("{:?}", value));
+                    }
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.new_lib_key);
+            let key = self.new_lib_key.trim().to_string();
+            if ui.button("Add key").clicked() && !key.is_empty() && !new_dict.contains_key(&key) {
+                new_dict.insert(key, plist::Value::String(String::new()));
+                self.new_lib_key.clear();
+            }
+        });
+
+        if new_dict != dict {
+            v.begin_modification("Edited glyph lib.", false);
+            v.with_glyph_mut(|glyph| glyph.lib = Lib::Plist(new_dict.clone()));
+            v.end_modification();
+        }
+    }
+}
+
+impl GlifWindow for GlyphWindow {
+    fn open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open;
+        if !open {
+            // Other glyphs may have changed by the time we're opened again.
+            self.font_unicodes = None;
+        }
+    }
+
+    fn build(&mut self, ctx: &Context, v: &mut Editor, _i: &mut Interface) {
+        if !self.open {
+            return;
+        }
+        if self.font_unicodes.is_none() {
+            self.font_unicodes = Some(v.sibling_unicodes().unwrap_or_default());
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Glyph")
+            .resizable(true)
+            .collapsible(true)
+            .open(&mut open)
+            .enabled(!v.is_modifying())
+            .constrain(true)
+            .default_width(200.)
+            .show(ctx, |ui| {
+                let name = v.with_glyph(|glyph| glyph.name.clone());
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    let new_name = egui_parsed_textfield(ui, "name", name.clone(), &mut self.edit_buf);
+                    if new_name != name {
+                        self.set_name(v, new_name);
+                    }
+                });
+
+                let unicodes = v.with_glyph(|glyph| glyph.unicode.clone());
+                let unicodes_s = format_unicodes(&unicodes);
+                ui.horizontal(|ui| {
+                    ui.label("Unicode");
+                    let new_unicodes =
+                        egui_parsed_textfield(ui, "unicodes", unicodes_s.clone(), &mut self.edit_buf);
+                    if new_unicodes != unicodes_s {
+                        self.set_unicodes(v, new_unicodes);
+                    }
+                });
+                for c in unicodes.iter() {
+                    if let Some(other) = self.font_unicodes.as_ref().and_then(|fu| fu.get(c)) {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            format!("U+{:04X} is also used by {}", *c as u32, other),
+                        );
+                    }
+                }
+
+                let width = v.advance_width();
+                ui.horizontal(|ui| {
+                    ui.label("Width");
+                    let new_width = egui_parsed_textfield(ui, "width", width, &mut self.edit_buf);
+                    if new_width != width {
+                        v.set_advance_width(new_width);
+                    }
+                });
+
+                let height = v.advance_height();
+                ui.horizontal(|ui| {
+                    ui.label("Height");
+                    let new_height = egui_parsed_textfield(ui, "height", height, &mut self.edit_buf);
+                    if new_height != height {
+                        v.set_advance_height(new_height);
+                    }
+                });
+
+                if let Some(error) = self.error.as_ref() {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.separator();
+
+                ui.label("Note");
+                let note = v.with_glyph(|glyph| glyph.note.clone()).unwrap_or_default();
+                let note_buf = self.edit_buf.entry("note".to_string()).or_insert(note.clone());
+                let response = ui.text_edit_multiline(note_buf);
+                // Only commit the note when we're done typing, so each keystroke isn't its own
+                // undo step.
+                if response.lost_focus() && *note_buf != note {
+                    let new_note = Some(note_buf.clone()).filter(|n| !n.is_empty());
+                    v.begin_modification("Edited glyph note.", false);
+                    v.with_glyph_mut(|glyph| glyph.note = new_note.clone());
+                    v.end_modification();
+                } else if !response.has_focus() {
+                    *note_buf = note;
+                }
+
+                ui.separator();
+
+                ui.collapsing("Lib", |ui| self.build_lib(ui, v));
+            });
+        self.set_open(open);
+    }
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
 use crate::{
-    contour_operations::ContourOperationBuild as _,
-    editor::Editor,
+    contour_operations::stack::Stage,
+    editor::{operation_stack::NewStage, Editor},
     user_interface::{gui::window::GlifWindow, Interface},
 };
 use egui::Context;
@@ -70,30 +70,29 @@ impl GlifWindow for InspectionWindow {
                             }
                         }
 
-                        if contour.operation().is_some() {
-                            if ui.button("Apply Contour Operation").clicked() {
-                                let outline = contour.operation().build(&contour);
-
-                                v.begin_modification("Modified contour with inspector.", true);
-                                v.get_active_layer_mut().outline.remove(ci);
-                                for c in outline {
-                                    v.get_active_layer_mut().outline.insert(ci, c);
-                                }
-                                v.end_modification();
-                                v.contour_idx = None;
-                                v.point_idx = None;
-                                v.selected.clear();
+                        let stages = v.operation_stack(ci);
+                        if !stages.is_empty() {
+                            if ui
+                                .button("Expand Contour Operations")
+                                .on_hover_text("Replaces the contour with what its operations make of it")
+                                .clicked()
+                            {
+                                v.expand_contour_operations(ci);
+                                return;
                             }
-                            if ui.button("Remove Contour Operation").clicked() {
-                                let ci =
-                                    v.contour_idx.expect("Expected a selected contour w/o one");
-
+                            if ui.button("Remove Contour Operations").clicked() {
                                 v.begin_modification("Modified contour with inspector.", true);
-                                v.get_active_layer_mut().outline[ci].set_operation(None);
+                                v.set_operation_stack(ci, vec![]);
                                 v.end_modification();
+                                v.operation_stack_preview = None;
+                                return;
                             }
                         }
 
+                        ui.collapsing("Operations", |ui| {
+                            operation_stack_ui(ui, v, ci, stages);
+                        });
+
                         let mut open = contour.is_open();
                         ui.checkbox(&mut open, "Open");
 
@@ -264,6 +263,86 @@ impl GlifWindow for InspectionWindow {
     }
 }
 
+/// Lists a contour's stacked operations, in the order they're applied, to turn them on and off,
+/// reorder, remove and add them, and to preview the contour as built through any one of them.
+fn operation_stack_ui(ui: &mut egui::Ui, v: &mut Editor, ci: usize, stages: Vec<Stage>) {
+    let mut edited: Option<Vec<Stage>> = None;
+    let previewing = v
+        .operation_stack_preview
+        .filter(|(pci, _)| *pci == ci)
+        .map(|(_, stage)| stage);
+    let mut preview = previewing;
+
+    if stages.is_empty() {
+        ui.label("None");
+    }
+    for (si, stage) in stages.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let mut enabled = stage.enabled;
+            if ui.checkbox(&mut enabled, stage.name()).changed() {
+                let mut new = stages.clone();
+                new[si].enabled = enabled;
+                edited = Some(new);
+            }
+            if ui.add_enabled(si > 0, egui::Button::new("⏶")).clicked() {
+                let mut new = stages.clone();
+                new.swap(si, si - 1);
+                edited = Some(new);
+            }
+            if ui
+                .add_enabled(si + 1 < stages.len(), egui::Button::new("⏷"))
+                .clicked()
+            {
+                let mut new = stages.clone();
+                new.swap(si, si + 1);
+                edited = Some(new);
+            }
+            if ui.button("✕").clicked() {
+                let mut new = stages.clone();
+                new.remove(si);
+                edited = Some(new);
+            }
+            if ui
+                .selectable_label(previewing == Some(si), "👁")
+                .on_hover_text("Preview the contour built through this operation")
+                .clicked()
+            {
+                preview = if previewing == Some(si) { None } else { Some(si) };
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("+ Dash").clicked() {
+            let mut new = stages.clone();
+            new.push(v.new_stage(NewStage::Dash, ci));
+            edited = Some(new);
+        }
+        if ui.button("+ Variable width stroke").clicked() {
+            let mut new = stages.clone();
+            new.push(v.new_stage(NewStage::VariableWidthStroke, ci));
+            edited = Some(new);
+        }
+        if ui.button("+ Nib stroke").clicked() {
+            let mut new = stages.clone();
+            new.push(v.new_stage(NewStage::Nib, ci));
+            edited = Some(new);
+        }
+    });
+
+    if let Some(new) = edited {
+        // Stages moved around, so whatever was being previewed isn't what it was.
+        preview = None;
+        v.begin_modification("Modified contour operations with inspector.", true);
+        v.set_operation_stack(ci, new);
+        v.end_modification();
+    }
+    if preview != previewing {
+        v.operation_stack_preview = preview.map(|stage| (ci, stage));
+        v.mark_preview_dirty();
+    }
+}
+
 fn point_equivalent<PD: PointData>(
     a: &dyn MFEKPointCommon<PD>,
     b: &dyn MFEKPointCommon<PD>,
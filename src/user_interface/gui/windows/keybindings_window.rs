@@ -0,0 +1,195 @@
+use crate::{
+    command::{self, key_sequence_to_string, sequences_conflict, Command, KeySequence},
+    editor::Editor,
+    user_interface::{gui::window::GlifWindow, InputPrompt, Interface},
+};
+use egui::Context;
+use strum::IntoEnumIterator as _;
+
+use std::collections::HashSet;
+
+pub struct KeybindingsWindow {
+    // is this window open?
+    open: bool,
+    // The bindings being edited, taken from the keymap when the window's opened.
+    bindings: Option<Vec<(Command, KeySequence)>>,
+    // The command keys are being recorded for, and whether they'll be added to its bindings
+    // rather than replace them.
+    recording_for: Option<(Command, bool)>,
+    filter: String,
+}
+
+impl KeybindingsWindow {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            bindings: None,
+            recording_for: None,
+            filter: String::new(),
+        }
+    }
+}
+
+/// For each binding, the commands of the others it conflicts with.
+fn conflicts(bindings: &[(Command, KeySequence)]) -> Vec<Vec<Command>> {
+    let mut ret = vec![vec![]; bindings.len()];
+    for a in 0..bindings.len() {
+        for b in a + 1..bindings.len() {
+            if sequences_conflict(&bindings[a].1, &bindings[b].1) {
+                ret[a].push(bindings[b].0);
+                ret[b].push(bindings[a].0);
+            }
+        }
+    }
+    ret
+}
+
+impl GlifWindow for KeybindingsWindow {
+    fn open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    fn build(&mut self, ctx: &Context, _v: &mut Editor, i: &mut Interface) {
+        if !self.open {
+            if self.recording_for.take().is_some() {
+                command::stop_recording();
+            }
+            self.bindings = None;
+            return;
+        }
+
+        let Self { open, bindings, recording_for, filter } = self;
+        let bindings = bindings.get_or_insert_with(command::keybindings);
+        egui::Window::new("Key Bindings")
+            .resizable(true)
+            .collapsible(true)
+            .open(open)
+            .constrain(true)
+            .default_width(420.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Filter");
+                    ui.text_edit_singleline(filter);
+                });
+                ui.separator();
+
+                let conflicts = conflicts(bindings);
+                let filter = filter.to_lowercase();
+                egui::ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+                    egui::Grid::new("keybindings").striped(true).show(ui, |ui| {
+                        for command in Command::iter() {
+                            if !command.to_string().to_lowercase().contains(&filter) {
+                                continue;
+                            }
+                            ui.label(command.to_string()).on_hover_text(command.type_().to_string());
+
+                            ui.vertical(|ui| {
+                                if recording_for.map(|(c, _)| c) == Some(command) {
+                                    let keys = command::recording().unwrap_or_default();
+                                    if keys.is_empty() {
+                                        ui.strong("Press keys…");
+                                    } else {
+                                        ui.strong(key_sequence_to_string(&keys));
+                                    }
+                                    return;
+                                }
+                                let mut bound = false;
+                                for (bi, (c, sequence)) in bindings.iter().enumerate() {
+                                    if *c != command {
+                                        continue;
+                                    }
+                                    bound = true;
+                                    let text = key_sequence_to_string(sequence);
+                                    // Bindings added this frame aren't checked until the next.
+                                    match conflicts.get(bi).filter(|with| !with.is_empty()) {
+                                        None => {
+                                            ui.label(text);
+                                        }
+                                        Some(with) => {
+                                            let with: Vec<String> = with.iter().map(|c| c.to_string()).collect();
+                                            ui.colored_label(egui::Color32::RED, text)
+                                                .on_hover_text(format!("Conflicts with {}", with.join(", ")));
+                                        }
+                                    }
+                                }
+                                if !bound {
+                                    ui.weak("Unbound");
+                                }
+                            });
+
+                            let recording = *recording_for;
+                            ui.horizontal(|ui| match recording {
+                                Some((c, adding)) if c == command => {
+                                    if ui.button("Done").clicked() {
+                                        if let Some(keys) = command::stop_recording().filter(|k| !k.is_empty()) {
+                                            if !adding {
+                                                bindings.retain(|(c, _)| *c != command);
+                                            }
+                                            bindings.push((command, keys));
+                                        }
+                                        *recording_for = None;
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        command::stop_recording();
+                                        *recording_for = None;
+                                    }
+                                }
+                                _ => {
+                                    ui.add_enabled_ui(recording.is_none(), |ui| {
+                                        if ui
+                                            .button("Record")
+                                            .on_hover_text("Replace the shortcut; press each key of a chord in turn")
+                                            .clicked()
+                                        {
+                                            command::start_recording();
+                                            *recording_for = Some((command, false));
+                                        }
+                                        if ui.button("Add").on_hover_text("Record another shortcut").clicked() {
+                                            command::start_recording();
+                                            *recording_for = Some((command, true));
+                                        }
+                                        if ui.button("Default").clicked() {
+                                            bindings.retain(|(c, _)| *c != command);
+                                            bindings.extend(
+                                                command::default_keybindings().into_iter().filter(|(c, _)| *c == command),
+                                            );
+                                        }
+                                    });
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.separator();
+                let conflicting = conflicts.iter().filter(|c| !c.is_empty()).count();
+                if conflicting > 0 {
+                    ui.colored_label(egui::Color32::RED, format!("{} shortcuts conflict", conflicting));
+                }
+                ui.horizontal(|ui| {
+                    let as_set = |b: &[(Command, KeySequence)]| b.iter().cloned().collect::<HashSet<_>>();
+                    let changed = as_set(bindings) != as_set(&command::keybindings());
+                    if ui
+                        .add_enabled(changed && conflicting == 0, egui::Button::new("Save"))
+                        .on_disabled_hover_text("Nothing to save, or shortcuts conflict")
+                        .clicked()
+                    {
+                        if let Err(message) = command::save_keybindings(bindings) {
+                            i.push_prompt(InputPrompt::Message { message });
+                        }
+                    }
+                    if ui.add_enabled(changed, egui::Button::new("Revert")).clicked() {
+                        *bindings = command::keybindings();
+                    }
+                    if ui.button("Reset All to Defaults").clicked() {
+                        *bindings = command::default_keybindings();
+                    }
+                });
+            });
+    }
+}
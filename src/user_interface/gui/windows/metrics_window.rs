@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use super::egui_parsed_textfield;
+use crate::{
+    editor::{metrics_keys::MetricsKeySide, Editor},
+    user_interface::{gui::window::GlifWindow, Interface},
+};
+use egui::Context;
+
+pub struct MetricsWindow {
+    // is this window open?
+    open: bool,
+    edit_buf: HashMap<String, String>,
+    // the last error from setting or resolving metrics keys
+    key_error: Option<String>,
+}
+
+impl MetricsWindow {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            edit_buf: HashMap::new(),
+            key_error: None,
+        }
+    }
+}
+
+impl GlifWindow for MetricsWindow {
+    fn open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    fn build(&mut self, ctx: &Context, v: &mut Editor, _i: &mut Interface) {
+        egui::Window::new("Metrics")
+            .resizable(true)
+            .collapsible(true)
+            .open(&mut self.open)
+            .enabled(!v.is_modifying())
+            .constrain(true)
+            .default_width(100.)
+            .show(ctx, |ui| {
+                let width = v.advance_width();
+                ui.horizontal(|ui| {
+                    ui.label("Advance");
+                    let new_width = egui_parsed_textfield(ui, "advance", width, &mut self.edit_buf);
+                    if new_width != width {
+                        v.set_advance_width(new_width);
+                    }
+                });
+
+                if let Some((lsb, rsb)) = v.sidebearings() {
+                    ui.horizontal(|ui| {
+                        ui.label("LSB");
+                        let new_lsb = egui_parsed_textfield(ui, "lsb", lsb, &mut self.edit_buf);
+                        if new_lsb != lsb {
+                            v.set_left_sidebearing(new_lsb);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("RSB");
+                        let new_rsb = egui_parsed_textfield(ui, "rsb", rsb, &mut self.edit_buf);
+                        if new_rsb != rsb {
+                            v.set_right_sidebearing(new_rsb);
+                        }
+                    });
+
+                    ui.separator();
+
+                    if ui.button("Center").clicked() {
+                        v.center_glyph();
+                    }
+                } else {
+                    ui.label("Glyph has no outlines.");
+                }
+
+                ui.collapsing("Keys", |ui| {
+                    for (side, label, id) in [
+                        (MetricsKeySide::Left, "LSB", "lsb_key"),
+                        (MetricsKeySide::Right, "RSB", "rsb_key"),
+                        (MetricsKeySide::Width, "Advance", "advance_key"),
+                    ] {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            let key = v.metrics_key(side).unwrap_or_default();
+                            let new_key = egui_parsed_textfield(ui, id, key.clone(), &mut self.edit_buf);
+                            if new_key != key {
+                                let new_key = Some(new_key).filter(|k| !k.trim().is_empty());
+                                self.key_error = v.set_metrics_key(side, new_key).err();
+                            }
+                        });
+                    }
+
+                    if ui.button("Update metrics from keys").clicked() {
+                        self.key_error = v.update_metrics_from_keys().err();
+                    }
+
+                    if let Some(error) = self.key_error.as_ref() {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+                });
+            });
+    }
+}
@@ -4,9 +4,14 @@ use egui::Ui;
 
 use super::textedit_buffer::EditBuffer;
 
+pub mod curvature_comb_window;
+pub mod glyph_window;
 pub mod grid_window;
 pub mod inspection_window;
+pub mod keybindings_window;
 pub mod layer_list;
+pub mod metrics_window;
+pub mod preferences_window;
 pub mod tool_window;
 
 pub fn egui_parsed_textfield<D>(
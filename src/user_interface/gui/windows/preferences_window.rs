@@ -0,0 +1,155 @@
+use crate::{
+    editor::Editor,
+    settings::{self, load_settings, save_settings, Settings},
+    user_interface::{gui::window::GlifWindow, InputPrompt, Interface},
+};
+use egui::{Color32, Context};
+
+pub struct PreferencesWindow {
+    // is this window open?
+    open: bool,
+    // The settings as they're saved, read the first time the window's opened.
+    saved: Option<Settings>,
+}
+
+impl PreferencesWindow {
+    pub fn new() -> Self {
+        Self { open: false, saved: None }
+    }
+}
+
+fn color_edit(ui: &mut egui::Ui, label: &str, argb: &mut u32) {
+    let [a, r, g, b] = argb.to_be_bytes();
+    let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+    ui.label(label);
+    if ui.color_edit_button_srgba(&mut color).changed() {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        *argb = u32::from_be_bytes([a, r, g, b]);
+    }
+    ui.end_row();
+}
+
+impl GlifWindow for PreferencesWindow {
+    fn open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    fn build(&mut self, ctx: &Context, _v: &mut Editor, i: &mut Interface) {
+        if !self.open {
+            return;
+        }
+        let saved = self.saved.get_or_insert_with(|| {
+            load_settings().unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                Settings::default()
+            })
+        });
+
+        let mut s = settings::current();
+        s.capture(i);
+        let before = s;
+        egui::Window::new("Preferences")
+            .resizable(true)
+            .collapsible(true)
+            .open(&mut self.open)
+            .constrain(true)
+            .default_width(240.)
+            .show(ctx, |ui| {
+                ui.collapsing("Grid", |ui| {
+                    ui.checkbox(&mut s.grid.show, "Show");
+                    ui.horizontal(|ui| {
+                        ui.label("Spacing");
+                        ui.add(egui::DragValue::new(&mut s.grid.spacing).speed(1.));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Offset");
+                        ui.add(egui::DragValue::new(&mut s.grid.offset).speed(1.));
+                    });
+                    let mut italic = s.grid.slope.is_some();
+                    ui.checkbox(&mut italic, "Italic");
+                    if !italic {
+                        s.grid.slope = None;
+                    } else if s.grid.slope.is_none() {
+                        s.grid.slope = Some(0.5);
+                    }
+                    if let Some(slope) = s.grid.slope.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Slope");
+                            ui.add(egui::DragValue::new(slope).speed(0.01));
+                        });
+                    }
+                });
+
+                ui.collapsing("Curvature Comb", |ui| {
+                    let comb = &mut s.curvature_comb;
+                    ui.checkbox(&mut comb.enabled, "Active");
+                    ui.checkbox(&mut comb.all_contours, "All contours");
+                    ui.checkbox(&mut comb.mark_discontinuities, "Mark discontinuities");
+                    ui.add(egui::Slider::new(&mut comb.density, 4..=128).text("teeth per curve"));
+                    ui.add(egui::Slider::new(&mut comb.scale, 1000f32..=200000f32).logarithmic(true).text("scale"));
+                });
+
+                ui.collapsing("Nudge", |ui| {
+                    ui.label("How far the arrow keys move, in font units");
+                    for (label, distance) in [
+                        ("Normal", &mut s.nudge.normal),
+                        ("Big", &mut s.nudge.big),
+                        ("Tiny", &mut s.nudge.tiny),
+                    ] {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.add(egui::DragValue::new(distance).speed(0.1));
+                        });
+                    }
+                });
+
+                ui.collapsing("Colors", |ui| {
+                    ui.checkbox(&mut s.paper_draw_guidelines, "Draw guidelines in paper mode");
+                    let c = &mut s.colors;
+                    egui::Grid::new("preference_colors").show(ui, |ui| {
+                        color_edit(ui, "Background", &mut c.background);
+                        color_edit(ui, "Paper background", &mut c.paper_background);
+                        color_edit(ui, "Right bearing", &mut c.right_bearing);
+                        color_edit(ui, "UFO guidelines", &mut c.ufo_guideline);
+                        color_edit(ui, "Measure", &mut c.measure);
+                        color_edit(ui, "Smart guides", &mut c.smart_guide);
+                        color_edit(ui, "Sidebearing handles", &mut c.sidebearing_handle);
+                        color_edit(ui, "Comb teeth", &mut c.comb_teeth);
+                        color_edit(ui, "Comb outline", &mut c.comb_outline);
+                        color_edit(ui, "Comb discontinuities", &mut c.comb_discontinuity);
+                        color_edit(ui, "Comb inflections", &mut c.comb_inflection);
+                    });
+                });
+
+                ui.separator();
+                if s != *saved {
+                    ui.weak("Unsaved changes");
+                }
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(s != *saved, egui::Button::new("Save")).clicked() {
+                        match save_settings(&s) {
+                            Ok(()) => *saved = s,
+                            Err(message) => i.push_prompt(InputPrompt::Message { message }),
+                        }
+                    }
+                    if ui.add_enabled(s != *saved, egui::Button::new("Revert")).clicked() {
+                        s = *saved;
+                    }
+                    if ui.button("Reset to Defaults").clicked() {
+                        s = Settings::default();
+                    }
+                });
+            });
+
+        s.grid.spacing = s.grid.spacing.max(1.);
+        s.grid.offset %= s.grid.spacing;
+        if s != before {
+            settings::set_current(s);
+            s.apply(i);
+        }
+    }
+}
@@ -10,7 +10,6 @@ use std::rc::Rc;
 use crate::render;
 //use crate::user_interface::gui::build_imgui_ui;
 use gl;
-use glifrenderer::grid::Grid;
 use glifrenderer::viewport::Viewport;
 use sdl2::video::GLContext;
 use skia_bindings::GrDirectContext;
@@ -22,7 +21,9 @@ use skia_safe::ColorType;
 use skia_safe::RCHandle;
 
 use crate::editor::Editor;
+pub use crate::render::kerning_preview::KerningPreview;
 pub use crate::render::measure::Measure;
+pub use crate::render::RenderState;
 pub use crate::user_interface::mouse_input::MouseInfo;
 
 use sdl2::{video::Window as SdlWindow, Sdl};
@@ -32,20 +33,18 @@ use self::gui::build_ui;
 use self::gui::window::WindowManager;
 pub use self::popout::PopoutWindow;
 
-/* Window */
-pub const PAPER_DRAW_GUIDELINES: bool = false;
-
+/// Everything needed to draw the editor is kept in a RenderState, which Interface derefs to, so the
+/// renderer can also be used without a window (see render::headless and the golden image tests).
+#[derive(shrinkwraprs::Shrinkwrap)]
+#[shrinkwrap(mutable)]
 pub struct Interface {
     prompts: Vec<InputPrompt>,
     sdl_context: Sdl,
     sdl_dpi: f32,
 
     pub context: Option<(f32, f32)>,
-    pub grid: Grid,
-    pub measure: Measure,
-    pub curvature_vis: bool,
-    pub mouse_info: MouseInfo,
-    pub viewport: Viewport,
+    #[shrinkwrap(main_field)]
+    pub state: RenderState,
 
     // OpenGL and Skia
     gl_ctx: GLContext,
@@ -86,15 +85,7 @@ impl Interface {
             sdl_dpi: f32::NAN,
 
             context: None,
-            grid: Grid::default(),
-            measure: Measure {
-                start_point: None,
-                end_point: None,
-                enabled: true,
-            },
-            curvature_vis: true,
-            mouse_info: MouseInfo::default(),
-            viewport: Viewport::default(),
+            state: RenderState::default(),
 
             gl_ctx,
             gr_context,
@@ -144,7 +135,7 @@ impl Interface {
         build_ui(egui_manager, v, self, wm);
         let canvas = sk_surface.canvas();
         canvas.clear(Color::BLACK);
-        render::render_frame(v, self, canvas);
+        render::render_frame(v, &mut self.state, canvas);
         canvas.save();
         let scale = 1. / self.os_dpi();
         canvas.scale((scale, scale));
@@ -243,4 +234,8 @@ pub enum InputPrompt {
         default: String,
         func: Rc<dyn Fn(&mut Editor, String)>,
     },
+    /// Tells the user something, e.g. that a file couldn't be read, until they dismiss it.
+    Message {
+        message: String,
+    },
 }